@@ -1,7 +1,9 @@
+mod greedy;
 mod heuristic;
 
 use crate::Color;
 use crate::graph::Graph;
+pub use greedy::{GreedyColoring, dsatur, dsatur_colors};
 pub use heuristic::HeuristicColoring;
 
 pub trait ColorAlgorithm<G>
@@ -0,0 +1,66 @@
+use crate::Color;
+use crate::algo::ColorAlgorithm;
+use crate::graph::Graph;
+use nohash::IntSet;
+use std::rc::Rc;
+
+/// Colors the graph greedily with the DSatur rule: always color next the node with the most
+/// distinct colors among its neighbors (ties broken by degree), using the smallest free color.
+///
+/// The number of colors used is an upper bound on the chromatic number, computed in
+/// `O(n²)` time.
+pub fn dsatur<G: Graph>(graph: &G) -> Vec<Color> {
+    let size = graph.size();
+    let mut colors: Vec<Option<Color>> = vec![None; size];
+    let mut neighbor_colors: Vec<IntSet<Color>> = vec![IntSet::default(); size];
+
+    for _ in 0..size {
+        let node = (0..size)
+            .filter(|&node| colors[node].is_none())
+            .max_by_key(|&node| (neighbor_colors[node].len(), graph.neighbors(node).len()))
+            .unwrap();
+
+        let color = (0..).find(|c| !neighbor_colors[node].contains(c)).unwrap();
+        colors[node] = Some(color);
+        for &neighbor in graph.neighbors(node) {
+            neighbor_colors[neighbor].insert(color);
+        }
+    }
+
+    colors.into_iter().map(Option::unwrap).collect()
+}
+
+/// Number of colors used by [`dsatur`].
+pub fn dsatur_colors<G: Graph>(graph: &G) -> usize {
+    used_colors(&dsatur(graph))
+}
+
+fn used_colors(colors: &[Color]) -> usize {
+    colors.iter().map(|&c| c + 1).max().unwrap_or(0)
+}
+
+/// A fast, incomplete coloring: succeeds if [`dsatur`] needs at most `color_num` colors.
+///
+/// A failure does not mean the graph is not `color_num`-colorable, use
+/// [`crate::algo::HeuristicColoring`] for an exact answer.
+pub struct GreedyColoring<G: Graph> {
+    color_num: usize,
+    graph: Rc<G>,
+}
+
+impl<G: Graph> GreedyColoring<G> {
+    pub fn create(color_num: usize, graph: Rc<G>) -> Self {
+        Self { color_num, graph }
+    }
+}
+
+impl<G: Graph> ColorAlgorithm<G> for GreedyColoring<G> {
+    fn color(&mut self) -> Option<Vec<Color>> {
+        let colors = dsatur(self.graph.as_ref());
+        (used_colors(&colors) <= self.color_num).then_some(colors)
+    }
+
+    fn graph(&self) -> &G {
+        &self.graph
+    }
+}
@@ -1,15 +1,21 @@
 use crate::algo::ColorAlgorithm;
 use crate::graph::Graph;
+use crate::graph::clique::max_clique;
 use crate::{Color, Node};
 use nohash::IntSet;
 use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 pub struct HeuristicColoring<G: Graph> {
     color_num: usize,
     graph: Rc<G>,
     colors: Vec<Option<Color>>,
     domains: Vec<IntSet<Color>>,
+    symmetry_breaking: bool,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    timed_out: bool,
 }
 
 impl<G: Graph> HeuristicColoring<G> {
@@ -22,9 +28,49 @@ impl<G: Graph> HeuristicColoring<G> {
             graph,
             colors: vec![None; size],
             domains,
+            symmetry_breaking: false,
+            timeout: None,
+            deadline: None,
+            timed_out: false,
         }
     }
 
+    /// Colors a maximum clique with distinct colors before searching, which removes the
+    /// permutations of the colors from the search. The clique is found by branch and bound, so
+    /// this pays off on hard instances only.
+    pub fn with_symmetry_breaking(mut self, symmetry_breaking: bool) -> Self {
+        self.symmetry_breaking = symmetry_breaking;
+        self
+    }
+
+    /// Gives up the search after `timeout`, see [`HeuristicColoring::timed_out`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Whether the last [`ColorAlgorithm::color`] gave up because of the timeout, so that its
+    /// [`None`] does not mean that there is no coloring.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// Colors the nodes of `clique` with colors `0, 1, ...`, removing these colors from
+    /// `unused_colors`. Returns false if the clique can not be colored.
+    fn color_clique(&mut self, clique: &[Node], unused_colors: &mut IntSet<Color>) -> bool {
+        if clique.len() > self.color_num {
+            return false;
+        }
+        for (color, &node) in clique.iter().enumerate() {
+            if !self.forward_check(node, color).0 {
+                return false;
+            }
+            self.colors[node] = Some(color);
+            unused_colors.remove(&color);
+        }
+        true
+    }
+
     /// Check if a node is already colored
     fn colored(&self, node: Node) -> bool {
         self.colors[node].is_some()
@@ -86,14 +132,20 @@ impl<G: Graph> HeuristicColoring<G> {
         (true, removals)
     }
 
+    /// Colors `node`, then the uncolored nodes in `unused_nodes`, which does not hold `node`,
+    /// the most constrained first.
     fn search(
         &mut self,
         node: Node,
         unused_colors: &mut IntSet<Color>,
         unused_nodes: &mut Vec<Node>,
     ) -> bool {
-        if unused_nodes.is_empty() {
-            return true;
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.timed_out = true;
+            return false;
         }
 
         let ordered_colors = self.order_colors(node, unused_colors);
@@ -116,10 +168,12 @@ impl<G: Graph> HeuristicColoring<G> {
             }
             let removed = unused_colors.remove(&color);
             self.colors[node] = Some(color);
+            if unused_nodes.is_empty() {
+                return true;
+            }
 
             unused_nodes.sort_by_key(|node| -(self.domains[*node].len() as isize));
-            let next = unused_nodes.last().cloned().unwrap();
-            unused_nodes.remove(unused_nodes.len() - 1);
+            let next = unused_nodes.pop().unwrap();
 
             if self.search(next, unused_colors, unused_nodes) {
                 return true;
@@ -140,11 +194,21 @@ impl<G: Graph> HeuristicColoring<G> {
 impl<G: Graph> ColorAlgorithm<G> for HeuristicColoring<G> {
     fn color(&mut self) -> Option<Vec<Color>> {
         let size = self.graph.size();
-        self.search(
-            0,
-            &mut (0..self.color_num).collect(),
-            &mut (0..size).collect(),
-        );
+        let mut unused_colors = (0..self.color_num).collect();
+        self.deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        self.timed_out = false;
+        if self.symmetry_breaking {
+            let clique = max_clique(self.graph.as_ref());
+            if !self.color_clique(&clique, &mut unused_colors) {
+                return None;
+            }
+        }
+
+        let mut unused_nodes: Vec<Node> = (0..size).filter(|&v| !self.colored(v)).collect();
+        if !unused_nodes.is_empty() {
+            let first = unused_nodes.remove(0);
+            self.search(first, &mut unused_colors, &mut unused_nodes);
+        }
         self.colors.clone().into_iter().collect()
     }
 
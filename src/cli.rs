@@ -0,0 +1,604 @@
+//! The command-line interface of the `ml_cnp` binary, see [`USAGE`].
+//!
+//! Every command writes its results to the file given by `--output`, and logs its progress to
+//! standard error.
+
+use crate::algo::{ColorAlgorithm, GreedyColoring, HeuristicColoring, dsatur_colors};
+use crate::graph::clique::{independence_bound, independence_number, max_clique};
+use crate::graph::fractional::fractional_chromatic;
+use crate::graph::spectral::Spectrum;
+use crate::graph::theta::lovasz_theta_complement;
+use crate::graph::{Graph, VecVecGraph};
+use crate::io::{
+    Format, GraphFormat, IoError, LayerRecord, PointFormat, load_graph, parse_points,
+    points_dimension, save_layer, to_sparse6, write_graph, write_graphml, write_points,
+};
+use crate::search::{ConfigError, SearchConfig, config_dimension};
+use crate::{Color, Node};
+use nalgebra::Point;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// How long `search --colors` tries to color a candidate, unless `--timeout` is given.
+const COLOR_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub const USAGE: &str = "\
+usage: ml_cnp <command> [options]
+
+commands:
+  search   grow unit distance graphs from a seed with a beam search
+             --config <file> | --preset <name> | --seed <points file>
+             --dist <d>         edge distance of the `--seed` points (1)
+             --layers <n>       depth of the search (5)
+             --width <w>        beam width of every layer (10)
+             --colors <k>       look for candidates that are not k-colorable
+             --found <file>     where to save them, as a layer file
+             --timeout <secs>   give up coloring a candidate after a while (60)
+             --checkpoint <file>  save progress to and resume from a file
+             --binary           write binary layer files
+             --output <file>    the last layer, as a layer file
+  color    color a graph with at most k colors
+             <graph>
+             --colors <k>       (5)
+             --algorithm <name> heuristic or dsatur (heuristic)
+             --timeout <secs>   give up the heuristic search after a while
+             --symmetry-breaking  pre-color a maximum clique, for the heuristic search
+             --dist <d>         edge distance, if the input is a point set (1)
+             --output <file>    the colors, or a colored GraphML file if it ends with .graphml
+  analyze  compute bounds on the chromatic number of a graph
+             <graph>
+             --dist <d>         edge distance, if the input is a point set (1)
+             --output <file>
+  convert  convert a graph or a point set to another format
+             <input> <output>
+             --dist <d>         edge distance, if the input is a point set (1)
+
+Point sets are files ending with .xyz, .json or .sym, other inputs are graphs in any format
+read by `load_graph`. Output formats follow the extension: .xyz, .json and .sym for point
+sets, .dimacs or .col, .edges, .g6, .s6 and .graphml for graphs, the native format otherwise.
+";
+
+/// An error of the command line.
+#[derive(Debug)]
+pub enum CliError {
+    /// The arguments are invalid.
+    Usage(String),
+    Io(IoError),
+    Config(ConfigError),
+    /// The command ran but did not succeed, e.g. no coloring was found.
+    Failed(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{message}, see `ml_cnp help`"),
+            CliError::Io(err) => err.fmt(f),
+            CliError::Config(err) => write!(f, "invalid configuration: {err}"),
+            CliError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for CliError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CliError::Io(err) => Some(err),
+            CliError::Config(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<IoError> for CliError {
+    fn from(err: IoError) -> Self {
+        CliError::Io(err)
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(err: std::io::Error) -> Self {
+        CliError::Io(err.into())
+    }
+}
+
+impl From<ConfigError> for CliError {
+    fn from(err: ConfigError) -> Self {
+        CliError::Config(err)
+    }
+}
+
+fn usage(message: impl Into<String>) -> CliError {
+    CliError::Usage(message.into())
+}
+
+/// Runs a command, `args` being the arguments after the program name.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::cli::run;
+///
+/// let dir = std::env::temp_dir();
+/// let input = dir.join(format!("ml-cnp-doc-{}.txt", std::process::id()));
+/// let output = dir.join(format!("ml-cnp-doc-{}.g6", std::process::id()));
+/// std::fs::write(&input, "3\n0 1\n1 2\n").unwrap();
+///
+/// let args = ["convert", input.to_str().unwrap(), output.to_str().unwrap()];
+/// run(&args.map(String::from)).unwrap();
+/// assert_eq!(std::fs::read_to_string(&output).unwrap(), "Bg\n");
+/// # std::fs::remove_file(input).unwrap();
+/// # std::fs::remove_file(output).unwrap();
+///
+/// assert!(run(&["color".to_string()]).is_err());
+/// ```
+pub fn run(args: &[String]) -> Result<(), CliError> {
+    let Some((command, args)) = args.split_first() else {
+        return Err(usage("missing command"));
+    };
+    match command.as_str() {
+        "search" => search(&Options::parse(
+            args,
+            &[
+                "config",
+                "preset",
+                "seed",
+                "dist",
+                "layers",
+                "width",
+                "colors",
+                "found",
+                "timeout",
+                "checkpoint",
+                "output",
+            ],
+            &["binary"],
+        )?),
+        "color" => color(&Options::parse(
+            args,
+            &["colors", "algorithm", "timeout", "dist", "output"],
+            &["symmetry-breaking"],
+        )?),
+        "analyze" => analyze(&Options::parse(args, &["dist", "output"], &[])?),
+        "convert" => convert(&Options::parse(args, &["dist"], &[])?),
+        _ => Err(usage(format!("unknown command `{command}`"))),
+    }
+}
+
+/// Parsed arguments: positional arguments and `--name value` options.
+struct Options {
+    positional: Vec<String>,
+    values: HashMap<String, String>,
+    flags: Vec<String>,
+}
+
+impl Options {
+    /// Parses arguments, accepting the options of `names` with a value and the `flags`
+    /// without.
+    fn parse(args: &[String], names: &[&str], flags: &[&str]) -> Result<Self, CliError> {
+        let mut options = Options {
+            positional: vec![],
+            values: HashMap::new(),
+            flags: vec![],
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                options.positional.push(arg.clone());
+                continue;
+            };
+            if flags.contains(&name) {
+                options.flags.push(name.to_string());
+            } else if names.contains(&name) {
+                let value = args
+                    .next()
+                    .ok_or_else(|| usage(format!("missing value for `--{name}`")))?;
+                options.values.insert(name.to_string(), value.clone());
+            } else {
+                return Err(usage(format!("unknown option `{arg}`")));
+            }
+        }
+        Ok(options)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    fn get<T: FromStr>(&self, name: &str) -> Result<Option<T>, CliError> {
+        self.values
+            .get(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| usage(format!("invalid value `{value}` for `--{name}`")))
+            })
+            .transpose()
+    }
+
+    fn get_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, CliError> {
+        Ok(self.get(name)?.unwrap_or(default))
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// The `--timeout`, in seconds.
+    fn timeout(&self) -> Result<Option<Duration>, CliError> {
+        let Some(secs) = self.get::<f64>("timeout")? else {
+            return Ok(None);
+        };
+        Duration::try_from_secs_f64(secs)
+            .map(Some)
+            .map_err(|_| usage(format!("invalid timeout `{secs}`")))
+    }
+
+    /// The `--dist`, which must be a positive number.
+    fn dist(&self) -> Result<f64, CliError> {
+        let dist: f64 = self.get_or("dist", 1.0)?;
+        if dist.is_finite() && dist > 0.0 {
+            Ok(dist)
+        } else {
+            Err(usage(format!(
+                "invalid distance `{dist}`, it must be positive"
+            )))
+        }
+    }
+
+    fn path(&self, name: &str) -> Option<PathBuf> {
+        self.values.get(name).map(PathBuf::from)
+    }
+
+    fn output(&self) -> Result<PathBuf, CliError> {
+        self.path("output")
+            .ok_or_else(|| usage("missing `--output <file>`"))
+    }
+
+    /// The positional arguments, checking their number.
+    fn positional<const N: usize>(&self, names: [&str; N]) -> Result<[&str; N], CliError> {
+        match self.positional.len() {
+            n if n < N => Err(usage(format!("missing `<{}>`", names[n]))),
+            n if n > N => Err(usage(format!(
+                "unexpected argument `{}`",
+                self.positional[N]
+            ))),
+            _ => Ok(std::array::from_fn(|i| self.positional[i].as_str())),
+        }
+    }
+}
+
+/// The extension of a path, in lower case.
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn point_format(path: &Path) -> Option<PointFormat> {
+    match extension(path).as_str() {
+        "xyz" => Some(PointFormat::Xyz),
+        "json" => Some(PointFormat::Json),
+        "sym" => Some(PointFormat::Symbolic),
+        _ => None,
+    }
+}
+
+/// A point set of any supported dimension.
+enum PointSet {
+    Two(Vec<Point<f64, 2>>),
+    Three(Vec<Point<f64, 3>>),
+    Four(Vec<Point<f64, 4>>),
+}
+
+impl PointSet {
+    /// Reads points in the dimension given by the file, see [`points_dimension`].
+    fn load(path: &Path, format: PointFormat) -> Result<Self, IoError> {
+        let desc = std::fs::read_to_string(path)?;
+        match points_dimension(&desc, format)? {
+            2 => Ok(PointSet::Two(parse_points(&desc, format)?)),
+            3 => Ok(PointSet::Three(parse_points(&desc, format)?)),
+            4 => Ok(PointSet::Four(parse_points(&desc, format)?)),
+            dim => Err(IoError::format(0, format!("unsupported dimension {dim}"))),
+        }
+    }
+
+    fn graph(&self, dist: f64) -> VecVecGraph {
+        match self {
+            PointSet::Two(points) => VecVecGraph::from_points(points, dist),
+            PointSet::Three(points) => VecVecGraph::from_points(points, dist),
+            PointSet::Four(points) => VecVecGraph::from_points(points, dist),
+        }
+    }
+
+    fn write<W: Write>(&self, writer: &mut W, format: PointFormat) -> Result<(), IoError> {
+        match self {
+            PointSet::Two(points) => write_points(writer, points, format),
+            PointSet::Three(points) => write_points(writer, points, format),
+            PointSet::Four(points) => write_points(writer, points, format),
+        }
+    }
+}
+
+/// Reads a graph, or the unit distance graph of a point set at `--dist`.
+fn read_graph(path: &Path, options: &Options) -> Result<VecVecGraph, CliError> {
+    let dist = options.dist()?;
+    match point_format(path) {
+        Some(format) => Ok(PointSet::load(path, format)?.graph(dist)),
+        None => Ok(load_graph(path)?),
+    }
+}
+
+fn create(path: &Path) -> Result<BufWriter<File>, CliError> {
+    Ok(BufWriter::new(File::create(path)?))
+}
+
+fn search(options: &Options) -> Result<(), CliError> {
+    options.positional([])?;
+    let sources = ["config", "preset", "seed"]
+        .iter()
+        .filter(|name| options.has(name))
+        .count();
+    if sources > 1 {
+        return Err(usage(
+            "expected only one of `--config`, `--preset` and `--seed`",
+        ));
+    }
+    // options that would otherwise be ignored
+    if options.has("dist") && !options.has("seed") {
+        return Err(usage("`--dist` only applies to `--seed`"));
+    }
+    for name in ["found", "timeout"] {
+        if options.has(name) && !options.has("colors") {
+            return Err(usage(format!("`--{name}` needs `--colors`")));
+        }
+    }
+
+    if let Some(path) = options.path("seed") {
+        let format = point_format(&path).unwrap_or(PointFormat::Xyz);
+        let dist = options.dist()?;
+        return match PointSet::load(&path, format)? {
+            PointSet::Two(seed) => run_search(SearchConfig::new(seed, dist), options),
+            PointSet::Three(seed) => run_search(SearchConfig::new(seed, dist), options),
+            PointSet::Four(seed) => run_search(SearchConfig::new(seed, dist), options),
+        };
+    }
+
+    let desc = match options.path("config") {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => format!(
+            "preset {}",
+            options.values.get("preset").map_or("tetrahedron", |s| s)
+        ),
+    };
+    match config_dimension(&desc)? {
+        2 => run_search(SearchConfig::<2>::parse(&desc)?, options),
+        3 => run_search(SearchConfig::<3>::parse(&desc)?, options),
+        4 => run_search(SearchConfig::<4>::parse(&desc)?, options),
+        dim => Err(usage(format!("unsupported dimension {dim}"))),
+    }
+}
+
+fn run_search<const D: usize>(config: SearchConfig<D>, options: &Options) -> Result<(), CliError> {
+    let output = options.output()?;
+    let format = if options.flag("binary") {
+        Format::Binary
+    } else {
+        Format::Text
+    };
+    let colors: Option<usize> = options.get("colors")?;
+    let timeout = options.timeout()?.unwrap_or(COLOR_TIMEOUT);
+
+    let search = config
+        .beam_search(options.get_or("layers", 5)?)
+        .with_beam_width(options.get_or("width", 10)?);
+    let seed = std::slice::from_ref(&config.seed);
+    // with a checkpoint file, an interrupted search is resumed by running it again
+    let result = match options.path("checkpoint") {
+        Some(path) => search.resume(seed, path)?,
+        None => search.run(seed),
+    };
+
+    for layer in &result.layers {
+        let best = layer.best.first().map_or(0.0, |s| s.score);
+        eprintln!(
+            "depth {}: {} candidates, {} kept, best score {best}",
+            layer.depth, layer.generated, layer.kept
+        );
+    }
+
+    // the results are saved in the coordinates of the seed, with their parent and move
+    let frontier: Vec<_> = result
+        .frontier
+        .iter()
+        .map(|record| LayerRecord {
+            points: config.denormalize(&record.points),
+            ..record.clone()
+        })
+        .collect();
+    save_layer(&output, &frontier, format)?;
+    eprintln!(
+        "{} point sets saved to {}",
+        frontier.len(),
+        output.display()
+    );
+
+    let Some(colors) = colors else {
+        return Ok(());
+    };
+    let undecided = AtomicUsize::new(0);
+    let found: Vec<_> = result
+        .frontier
+        .par_iter()
+        .enumerate()
+        .filter(|(_, record)| {
+            // only an exhaustive search proves that there is no coloring, the spectral bound
+            // comes from a floating point eigensolver
+            let graph = Rc::new(config.graph(&record.points));
+            let mut algo = HeuristicColoring::create(colors, graph).with_timeout(timeout);
+            let coloring = algo.color();
+            if algo.timed_out() {
+                undecided.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+            coloring.is_none()
+        })
+        .map(|(i, _)| frontier[i].clone())
+        .collect();
+    eprintln!("{} point sets are not {colors}-colorable", found.len());
+    let undecided = undecided.into_inner();
+    if undecided > 0 {
+        eprintln!(
+            "{undecided} point sets undecided after {} s",
+            timeout.as_secs_f64()
+        );
+    }
+    if let Some(path) = options.path("found") {
+        save_layer(&path, &found, format)?;
+    }
+    Ok(())
+}
+
+fn color(options: &Options) -> Result<(), CliError> {
+    let [input] = options.positional(["graph"])?;
+    let output = options.output()?;
+    let colors: usize = options.get_or("colors", 5)?;
+    let timeout = options.timeout()?;
+    let algorithm = options.values.get("algorithm").map_or("heuristic", |s| s);
+    if algorithm == "dsatur" {
+        for name in ["timeout", "symmetry-breaking"] {
+            if options.has(name) || options.flag(name) {
+                return Err(usage(format!(
+                    "`--{name}` only applies to the heuristic search"
+                )));
+            }
+        }
+    }
+    let graph = Rc::new(read_graph(Path::new(input), options)?);
+
+    let coloring = match algorithm {
+        "heuristic" => {
+            let mut algo = HeuristicColoring::create(colors, graph.clone())
+                .with_symmetry_breaking(options.flag("symmetry-breaking"));
+            if let Some(timeout) = timeout {
+                algo = algo.with_timeout(timeout);
+            }
+            let coloring = algo.color();
+            if algo.timed_out() {
+                return Err(CliError::Failed(format!(
+                    "no {colors}-coloring found in {} s",
+                    timeout.unwrap().as_secs_f64()
+                )));
+            }
+            coloring
+        }
+        "dsatur" => GreedyColoring::create(colors, graph.clone()).color(),
+        name => return Err(usage(format!("unknown algorithm `{name}`"))),
+    };
+    let Some(coloring) = coloring else {
+        return Err(CliError::Failed(format!("no {colors}-coloring found")));
+    };
+
+    write_coloring(&output, graph.as_ref(), &coloring)?;
+    eprintln!(
+        "{} nodes colored with {} colors, saved to {}",
+        graph.size(),
+        coloring.iter().map(|&c| c + 1).max().unwrap_or(0),
+        output.display()
+    );
+    Ok(())
+}
+
+/// Writes a coloring as a line `<node> <color>` per node, or as GraphML.
+fn write_coloring(path: &Path, graph: &VecVecGraph, coloring: &[Color]) -> Result<(), CliError> {
+    let mut writer = create(path)?;
+    if extension(path) == "graphml" {
+        write_graphml(&mut writer, graph, Some(coloring))?;
+    } else {
+        writeln!(writer, "# <node> <color>")?;
+        for (node, color) in coloring.iter().enumerate() {
+            writeln!(writer, "{node} {color}")?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn analyze(options: &Options) -> Result<(), CliError> {
+    let [input] = options.positional(["graph"])?;
+    let output = options.output()?;
+    let graph = read_graph(Path::new(input), options)?;
+
+    let degrees = (0..graph.size()).map(|v: Node| graph.neighbors(v).len());
+    let spectrum = Spectrum::new(&graph);
+    let mut report = vec![
+        ("nodes", graph.size().to_string()),
+        ("edges", (degrees.clone().sum::<usize>() / 2).to_string()),
+        ("max degree", degrees.max().unwrap_or(0).to_string()),
+        ("clique number", max_clique(&graph).len().to_string()),
+        (
+            "independence number",
+            independence_number(&graph).to_string(),
+        ),
+        ("independence bound", independence_bound(&graph).to_string()),
+        ("spectral bound", spectrum.lower_bound().to_string()),
+    ];
+    match fractional_chromatic(&graph) {
+        Ok(fractional) => {
+            report.push(("fractional chromatic number", fractional.value.to_string()))
+        }
+        Err(err) => eprintln!("no fractional chromatic number: {err}"),
+    }
+    report.push((
+        "lovasz theta",
+        lovasz_theta_complement(&graph).value.to_string(),
+    ));
+    report.push(("dsatur colors", dsatur_colors(&graph).to_string()));
+
+    let mut writer = create(&output)?;
+    for (name, value) in report {
+        writeln!(writer, "{name}: {value}")?;
+    }
+    writer.flush()?;
+    eprintln!("analysis saved to {}", output.display());
+    Ok(())
+}
+
+fn convert(options: &Options) -> Result<(), CliError> {
+    let [input, output] = options.positional(["input", "output"])?;
+    let (input, output) = (Path::new(input), Path::new(output));
+
+    match (point_format(input), point_format(output)) {
+        (Some(from), Some(to)) => {
+            let points = PointSet::load(input, from)?;
+            let mut writer = create(output)?;
+            points.write(&mut writer, to)?;
+            writer.flush()?;
+        }
+        (None, Some(_)) => {
+            return Err(usage("a graph can not be converted to a point set"));
+        }
+        (_, None) => {
+            let graph = read_graph(input, options)?;
+            let mut writer = create(output)?;
+            match extension(output).as_str() {
+                "dimacs" | "col" => write_graph(&mut writer, &graph, GraphFormat::Dimacs)?,
+                "edges" => write_graph(&mut writer, &graph, GraphFormat::EdgeList)?,
+                "g6" => write_graph(&mut writer, &graph, GraphFormat::Graph6)?,
+                "s6" => writeln!(writer, "{}", to_sparse6(&graph))?,
+                "graphml" => write_graphml(&mut writer, &graph, None)?,
+                _ => write_graph(&mut writer, &graph, GraphFormat::Native)?,
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use nalgebra::Point3;
+use nalgebra::{Point2, Point3};
 use std::f64::consts::SQRT_2;
 
 pub const EPS: f64 = 1e-8;
@@ -12,4 +12,11 @@ pub const INIT_POINTS: [Point3<f64>; 4] = [
     Point3::new(1.0, 1.0, 1.0),
 ];
 
+/// An equilateral triangle with side [`DIST`], the planar counterpart of [`INIT_POINTS`].
+pub const INIT_POINTS_2D: [Point2<f64>; 3] = [
+    Point2::new(0.0, 0.0),
+    Point2::new(SQRT_2, 0.0),
+    Point2::new(SQRT_2 / 2.0, 1.224744871391589),
+];
+
 pub const WL_TEST_ROUNDS: usize = 5;
@@ -0,0 +1,177 @@
+mod expr;
+mod real;
+
+use crate::graph::VecVecGraph;
+use crate::graph::hash::Hashable;
+use nalgebra::Point3;
+use rayon::prelude::*;
+pub use real::Real;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// A 3D point with exact coordinates.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ExactPoint {
+    pub coords: [Real; 3],
+}
+
+impl ExactPoint {
+    pub fn new(x: Real, y: Real, z: Real) -> Self {
+        Self { coords: [x, y, z] }
+    }
+
+    /// The exact point with the same coordinates as a float point.
+    pub fn from_point(point: &Point3<f64>) -> Self {
+        Self::new(
+            Real::from_f64(point.x),
+            Real::from_f64(point.y),
+            Real::from_f64(point.z),
+        )
+    }
+
+    /// Closest float approximation of the point.
+    pub fn to_point(&self) -> Point3<f64> {
+        let [x, y, z] = &self.coords;
+        Point3::new(x.to_f64(), y.to_f64(), z.to_f64())
+    }
+
+    fn zip(&self, other: &Self, f: impl Fn(&Real, &Real) -> Real) -> Self {
+        let [x1, y1, z1] = &self.coords;
+        let [x2, y2, z2] = &other.coords;
+        Self::new(f(x1, x2), f(y1, y2), f(z1, z2))
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        self.zip(other, |a, b| a + b)
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.zip(other, |a, b| a - b)
+    }
+
+    pub fn scale(&self, factor: &Real) -> Self {
+        let [x, y, z] = &self.coords;
+        Self::new(x * factor, y * factor, z * factor)
+    }
+
+    pub fn dot(&self, other: &Self) -> Real {
+        let [x1, y1, z1] = &self.coords;
+        let [x2, y2, z2] = &other.coords;
+        &(&(x1 * x2) + &(y1 * y2)) + &(z1 * z2)
+    }
+
+    pub fn cross(&self, other: &Self) -> Self {
+        let [x1, y1, z1] = &self.coords;
+        let [x2, y2, z2] = &other.coords;
+        Self::new(
+            &(y1 * z2) - &(z1 * y2),
+            &(z1 * x2) - &(x1 * z2),
+            &(x1 * y2) - &(y1 * x2),
+        )
+    }
+
+    pub fn norm_squared(&self) -> Real {
+        self.dot(self)
+    }
+}
+
+/// Exact counterpart of [`crate::search::rotate`].
+///
+/// The rotation by angle `θ` around the axis `k = axis_b - axis_a` is applied with Rodrigues'
+/// formula `cos θ·w + sin θ·(k × w)/|k| + (1 - cos θ)(k·w)k/|k|²`, where `cos θ` is rational in
+/// the coordinates and only `sin θ / |k|` needs a square root.
+///
+/// # Returns
+///
+/// The points rotated by `θ` and by `-θ`, or [`None`] if the target lies on the axis, is too
+/// close to it to reach distance `√dist_sq`, or if the rotation is not expressible in a
+/// multiquadratic field.
+pub fn rotate(
+    points: &[ExactPoint],
+    target: &ExactPoint,
+    axis_a: &ExactPoint,
+    axis_b: &ExactPoint,
+    dist_sq: &Real,
+) -> Option<(Vec<ExactPoint>, Vec<ExactPoint>)> {
+    let k = axis_b.sub(axis_a);
+    let k_sq = k.norm_squared();
+    let v = target.sub(axis_a);
+    let v_k = v.dot(&k);
+    // squared distance of the target to the axis
+    let r_sq = &v.norm_squared() - &(&(&v_k * &v_k) / &k_sq);
+    if r_sq.is_zero() {
+        return None;
+    }
+
+    let two = Real::from_integer(2);
+    let cos = &Real::one() - &(dist_sq / &(&two * &r_sq));
+    let one_minus_cos = &Real::one() - &cos;
+    let sin_over_k = (&(&Real::one() - &(&cos * &cos)) / &k_sq).sqrt()?;
+
+    let rotated = |sin_over_k: &Real| {
+        points
+            .iter()
+            .map(|p| {
+                let w = p.sub(axis_a);
+                let along = &(&k.dot(&w) * &one_minus_cos) / &k_sq;
+                axis_a
+                    .add(&w.scale(&cos))
+                    .add(&k.cross(&w).scale(sin_over_k))
+                    .add(&k.scale(&along))
+            })
+            .collect()
+    };
+
+    Some((rotated(&sin_over_k), rotated(&-&sin_over_k)))
+}
+
+/// Exact counterpart of [`crate::search::merge`]: common points first, then the remaining
+/// points of `points1` and of `points2`.
+pub fn merge(points1: &[ExactPoint], points2: &[ExactPoint]) -> Vec<ExactPoint> {
+    let set1: HashSet<_> = points1.iter().collect();
+    let set2: HashSet<_> = points2.iter().collect();
+
+    let mut res: Vec<_> = points1
+        .iter()
+        .filter(|p| set2.contains(p))
+        .cloned()
+        .collect();
+    res.extend(points1.iter().filter(|p| !set2.contains(p)).cloned());
+    res.extend(points2.iter().filter(|p| !set1.contains(p)).cloned());
+
+    res
+}
+
+/// Exact counterpart of [`crate::search::next_layer`], for target squared distance `dist_sq`.
+///
+/// Moves whose rotation is not exactly representable are skipped, so the layer can be
+/// smaller than its float counterpart, but every edge of every graph is exact.
+pub fn next_layer(graphs: &[Vec<ExactPoint>], dist_sq: &Real) -> Vec<Vec<ExactPoint>> {
+    let graph_set = Mutex::new(HashMap::new());
+
+    graphs.par_iter().for_each(|graph| {
+        let len = graph.len();
+        for i in 0..len {
+            for j in i + 1..len {
+                for p in graph {
+                    if *p == graph[i] || *p == graph[j] {
+                        continue;
+                    }
+
+                    let Some((rotated1, rotated2)) =
+                        rotate(graph, p, &graph[i], &graph[j], dist_sq)
+                    else {
+                        continue;
+                    };
+                    for rotated in [rotated1, rotated2] {
+                        let merged = merge(&rotated, graph);
+                        let hash = VecVecGraph::from_exact_points(&merged, dist_sq).hash();
+                        graph_set.lock().unwrap().entry(hash).or_insert(merged);
+                    }
+                }
+            }
+        }
+    });
+
+    graph_set.into_inner().unwrap().into_values().collect()
+}
@@ -0,0 +1,253 @@
+use crate::exact::Real;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use std::str::FromStr;
+
+/// The largest exponent accepted by `^`, to keep the numbers small.
+const MAX_EXPONENT: u32 = 64;
+/// The largest exponent of a number in scientific notation, beyond the range of `f64`.
+const MAX_DECIMAL_EXPONENT: i64 = 400;
+/// The deepest nesting of parentheses, roots and signs, to keep the recursion off the end of
+/// the stack.
+const MAX_DEPTH: usize = 100;
+
+/// Parses arithmetic expressions such as `(1+sqrt(3))/2` or `-1.5e2*sqrt(2/3)`, with `+`, `-`,
+/// `*`, `/`, integer powers `^`, parentheses and `sqrt`. Numbers are read exactly, so `0.1` is
+/// `1/10`. The output of [`Real`]'s `Display` is read back.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::exact::Real;
+///
+/// let x: Real = "(1+sqrt(3))/2".parse().unwrap();
+/// assert_eq!(&(&x + &x) - &Real::one(), Real::from_integer(3).sqrt().unwrap());
+/// assert_eq!(x.to_string().parse::<Real>(), Ok(x));
+///
+/// assert!("sqrt(-1)".parse::<Real>().is_err());
+/// ```
+impl FromStr for Real {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            input: s.as_bytes(),
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.expr()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(value),
+            Some(c) => Err(parser.error(&format!("unexpected `{}`", c as char))),
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{message} at column {}", self.pos + 1)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    /// Skips whitespace and consumes `c` if it comes next.
+    fn eat(&mut self, c: u8) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// The product, or an error if the radicands grow too large for [`Real`].
+    fn mul(&self, a: &Real, b: &Real) -> Result<Real, String> {
+        a.checked_mul(b)
+            .ok_or_else(|| self.error("number too large"))
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", c as char)))
+        }
+    }
+
+    /// `expr = term (('+' | '-') term)*`
+    fn expr(&mut self) -> Result<Real, String> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat(b'+') {
+                value = &value + &self.term()?;
+            } else if self.eat(b'-') {
+                value = &value - &self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// `term = factor (('*' | '/') factor)*`
+    fn term(&mut self) -> Result<Real, String> {
+        let mut value = self.factor()?;
+        loop {
+            if self.eat(b'*') {
+                let factor = self.factor()?;
+                value = self.mul(&value, &factor)?;
+            } else if self.eat(b'/') {
+                let divisor = self.factor()?;
+                if divisor.is_zero() {
+                    return Err(self.error("division by zero"));
+                }
+                let inverse = divisor
+                    .checked_inverse()
+                    .ok_or_else(|| self.error("number too large"))?;
+                value = self.mul(&value, &inverse)?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Result<Real, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("expression nested too deeply"));
+        }
+        self.depth += 1;
+        let value = self.signed();
+        self.depth -= 1;
+        value
+    }
+
+    /// `factor = ('+' | '-') factor | atom ('^' integer)?`
+    fn signed(&mut self) -> Result<Real, String> {
+        if self.eat(b'-') {
+            return Ok(-self.factor()?);
+        }
+        if self.eat(b'+') {
+            return self.factor();
+        }
+
+        let base = self.atom()?;
+        if !self.eat(b'^') {
+            return Ok(base);
+        }
+        let negative = self.eat(b'-');
+        self.skip_whitespace();
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let exponent: u32 = std::str::from_utf8(&self.input[start..self.pos])
+            .unwrap()
+            .parse()
+            .ok()
+            .filter(|&e| e <= MAX_EXPONENT)
+            .ok_or_else(|| self.error("expected a small integer exponent"))?;
+
+        let mut value = Real::one();
+        for _ in 0..exponent {
+            value = self.mul(&value, &base)?;
+        }
+        if negative {
+            if value.is_zero() {
+                return Err(self.error("division by zero"));
+            }
+            value = value
+                .checked_inverse()
+                .ok_or_else(|| self.error("number too large"))?;
+        }
+        Ok(value)
+    }
+
+    /// `atom = '(' expr ')' | 'sqrt' '(' expr ')' | number`
+    fn atom(&mut self) -> Result<Real, String> {
+        self.skip_whitespace();
+        if self.eat(b'(') {
+            let value = self.expr()?;
+            self.expect(b')')?;
+            return Ok(value);
+        }
+        if self.input[self.pos..].starts_with(b"sqrt") {
+            self.pos += 4;
+            self.expect(b'(')?;
+            let value = self.expr()?;
+            self.expect(b')')?;
+            return value
+                .sqrt()
+                .ok_or_else(|| self.error(&format!("no square root of {value}")));
+        }
+        self.number()
+    }
+
+    /// A decimal number with an optional fraction and exponent, read exactly.
+    fn number(&mut self) -> Result<Real, String> {
+        let start = self.pos;
+        let mut digits = String::new();
+        // the value is `digits · 10^scale`
+        let mut scale = 0i64;
+        let mut fraction = false;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() {
+                digits.push(c as char);
+                if fraction {
+                    scale -= 1;
+                }
+            } else if c == b'.' && !fraction {
+                fraction = true;
+            } else {
+                break;
+            }
+            self.pos += 1;
+        }
+        if digits.is_empty() {
+            self.pos = start;
+            return Err(self.error("expected a number"));
+        }
+
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let mark = self.pos;
+            self.pos += 1;
+            let exponent_start = self.pos;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+            match std::str::from_utf8(&self.input[exponent_start..self.pos])
+                .unwrap()
+                .parse::<i64>()
+            {
+                Ok(exponent) if exponent.abs() <= MAX_DECIMAL_EXPONENT => scale += exponent,
+                Ok(_) => return Err(self.error("exponent out of range")),
+                Err(_) => self.pos = mark,
+            }
+        }
+
+        let mantissa: BigInt = digits.parse().unwrap();
+        let power = num_traits::pow(BigInt::from(10), scale.unsigned_abs() as usize);
+        let value = if scale >= 0 {
+            BigRational::from_integer(mantissa * power)
+        } else {
+            BigRational::new(mantissa, power)
+        };
+        Ok(Real::from_rational(value))
+    }
+}
@@ -0,0 +1,440 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Trial division bound used when extracting square roots of rationals and factoring radicands.
+const TRIAL_DIVISION_LIMIT: u64 = 1 << 16;
+
+/// An exact real number in a multiquadratic field `Q(√p1, √p2, ...)`.
+///
+/// The number is stored as a sum `q1·√r1 + q2·√r2 + ...` of rational multiples of square roots
+/// of distinct square-free integers. Those square roots are linearly independent over `Q`, so
+/// the representation is unique, and equality is exact.
+///
+/// The field is not fixed in advance: [`Real::sqrt`] adjoins new square roots of integers when
+/// needed, and denests square roots of field elements when they lie in a multiquadratic field.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::exact::Real;
+///
+/// let two = Real::from_integer(2);
+/// let sqrt2 = two.sqrt().unwrap();
+/// assert_eq!(&sqrt2 * &sqrt2, two);
+///
+/// // √(3 + 2√2) = 1 + √2
+/// let x = Real::from_integer(3) + &Real::from_integer(2) * &sqrt2;
+/// assert_eq!(x.sqrt(), Some(Real::one() + sqrt2));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Real {
+    /// Square-free radicand to its non-zero rational coefficient.
+    terms: BTreeMap<u64, BigRational>,
+}
+
+impl Real {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn one() -> Self {
+        Self::from_integer(1)
+    }
+
+    pub fn from_integer(value: i64) -> Self {
+        Self::from_rational(BigRational::from_integer(value.into()))
+    }
+
+    pub fn from_rational(value: BigRational) -> Self {
+        Self::from_term(1, value)
+    }
+
+    /// The exact value of a finite float, as a dyadic rational.
+    pub fn from_f64(value: f64) -> Self {
+        Self::from_rational(BigRational::from_float(value).expect("float must be finite"))
+    }
+
+    fn from_term(radicand: u64, coefficient: BigRational) -> Self {
+        let mut terms = BTreeMap::new();
+        if !coefficient.is_zero() {
+            terms.insert(radicand, coefficient);
+        }
+        Self { terms }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Returns the value if it is rational.
+    pub fn as_rational(&self) -> Option<BigRational> {
+        match self.terms.len() {
+            0 => Some(BigRational::zero()),
+            1 => self.terms.get(&1).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Closest `f64` approximation of the number.
+    pub fn to_f64(&self) -> f64 {
+        self.terms
+            .iter()
+            .map(|(&radicand, q)| q.to_f64().unwrap() * (radicand as f64).sqrt())
+            .sum()
+    }
+
+    fn add_term(&mut self, radicand: u64, coefficient: BigRational) {
+        let entry = self.terms.entry(radicand).or_insert_with(BigRational::zero);
+        *entry += coefficient;
+        if entry.is_zero() {
+            self.terms.remove(&radicand);
+        }
+    }
+
+    /// Primes occurring in any radicand, in increasing order, or [`None`] if a radicand can not
+    /// be factored.
+    fn primes(&self) -> Option<Vec<u64>> {
+        let mut primes = vec![];
+        for &r in self.terms.keys() {
+            primes.extend(factor(r)?);
+        }
+        primes.sort();
+        primes.dedup();
+        Some(primes)
+    }
+
+    /// The image under the field automorphism `√p ↦ -√p`.
+    fn conjugate(&self, p: u64) -> Self {
+        let terms = self
+            .terms
+            .iter()
+            .map(|(&r, q)| (r, if r.is_multiple_of(p) { -q } else { q.clone() }))
+            .collect();
+        Self { terms }
+    }
+
+    /// Splits the number as `a + b·√p`, where `a` and `b` do not involve `√p`.
+    fn split(&self, p: u64) -> (Self, Self) {
+        let mut a = Self::zero();
+        let mut b = Self::zero();
+        for (&r, q) in &self.terms {
+            if r.is_multiple_of(p) {
+                b.add_term(r / p, q.clone());
+            } else {
+                a.add_term(r, q.clone());
+            }
+        }
+        (a, b)
+    }
+
+    /// The product, or [`None`] if a radicand of the product does not fit in a `u64`.
+    pub fn checked_mul(&self, rhs: &Real) -> Option<Real> {
+        let mut res = Real::zero();
+        for (&r1, q1) in &self.terms {
+            for (&r2, q2) in &rhs.terms {
+                // √r1·√r2 = g·√(r1·r2 / g²) with g = gcd(r1, r2)
+                let g = gcd(r1, r2);
+                let radicand = (r1 / g).checked_mul(r2 / g)?;
+                res.add_term(radicand, q1 * q2 * BigRational::from_integer(g.into()));
+            }
+        }
+        Some(res)
+    }
+
+    /// Multiplicative inverse, by multiplying with conjugates until the denominator is rational.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number is zero, or if a radicand overflows as in [`Real::checked_mul`] or
+    /// can not be factored.
+    pub fn inverse(&self) -> Self {
+        assert!(!self.is_zero(), "division by zero");
+        self.checked_inverse().expect("radicand too large")
+    }
+
+    /// The inverse, or [`None`] if the number is zero or a radicand overflows or has two prime
+    /// factors above the trial division bound.
+    pub fn checked_inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        let mut numerator = Self::one();
+        let mut denominator = self.clone();
+        while let Some(&p) = denominator.primes()?.last() {
+            let conjugate = denominator.conjugate(p);
+            numerator = numerator.checked_mul(&conjugate)?;
+            denominator = denominator.checked_mul(&conjugate)?;
+        }
+        let denominator = denominator.as_rational().unwrap();
+        Some(numerator.scale(&denominator.recip()))
+    }
+
+    fn scale(&self, factor: &BigRational) -> Self {
+        if factor.is_zero() {
+            return Self::zero();
+        }
+        let terms = self.terms.iter().map(|(&r, q)| (r, q * factor)).collect();
+        Self { terms }
+    }
+
+    /// The non-negative square root, if it lies in a multiquadratic field.
+    ///
+    /// Square roots of positive rationals always exist (unless the rational is too large to
+    /// factor). For other numbers `x = a + b·√p`, a root `c + d·√p` exists only if the norm
+    /// `a² - p·b²` is a square, in which case it is found by denesting. Returns [`None`] for
+    /// negative numbers, for roots that need a nested radical and when a radicand overflows or
+    /// can not be factored.
+    pub fn sqrt(&self) -> Option<Self> {
+        if let Some(q) = self.as_rational() {
+            return rational_sqrt(&q);
+        }
+        if self.to_f64() < 0.0 {
+            return None;
+        }
+
+        let p = *self.primes()?.last().unwrap();
+        let (a, b) = self.split(p);
+        let norm = &a.checked_mul(&a)?
+            - &b.checked_mul(&b)?
+                .scale(&BigRational::from_integer(p.into()));
+        let n = norm.sqrt()?;
+        let half = BigRational::new(1.into(), 2.into());
+
+        for c_squared in [(&a + &n).scale(&half), (&a - &n).scale(&half)] {
+            let Some(c) = c_squared.sqrt() else {
+                continue;
+            };
+            if c.is_zero() {
+                continue;
+            }
+            let d = b.checked_mul(&(&c + &c).checked_inverse()?)?;
+            let root = &c + &d.checked_mul(&Self::from_term(p, BigRational::one()))?;
+            if root.checked_mul(&root)? == *self {
+                return Some(if root.to_f64() < 0.0 { -root } else { root });
+            }
+        }
+        None
+    }
+}
+
+/// Prime factors of a square-free number, if at most one of them is above the trial division
+/// bound.
+fn factor(mut n: u64) -> Option<Vec<u64>> {
+    let mut primes = vec![];
+    let mut p = 2;
+    while p < TRIAL_DIVISION_LIMIT && p * p <= n {
+        if n.is_multiple_of(p) {
+            primes.push(p);
+            n /= p;
+        }
+        p += 1;
+    }
+    if n >= TRIAL_DIVISION_LIMIT * TRIAL_DIVISION_LIMIT {
+        return None;
+    }
+    // no factor below the limit, so the cofactor is prime
+    if n > 1 {
+        primes.push(n);
+    }
+    Some(primes)
+}
+
+/// Writes `n` as `s² · r` with `r` square-free, if the factorization is tractable.
+fn square_free_part(n: &BigInt) -> Option<(BigInt, u64)> {
+    let mut n = n.clone();
+    let mut square = BigInt::one();
+    let mut radicand = 1u64;
+    let mut p = 2u64;
+    while p < TRIAL_DIVISION_LIMIT && BigInt::from(p * p) <= n {
+        let prime = BigInt::from(p);
+        while (&n % &prime).is_zero() {
+            n /= &prime;
+            if (&n % &prime).is_zero() {
+                n /= &prime;
+                square *= &prime;
+            } else {
+                radicand = radicand.checked_mul(p)?;
+                break;
+            }
+        }
+        p += 1;
+    }
+
+    let root = n.sqrt();
+    if &root * &root == n {
+        square *= root;
+    } else if n < BigInt::from(TRIAL_DIVISION_LIMIT * TRIAL_DIVISION_LIMIT) {
+        // no factor below the limit, so the cofactor is prime
+        radicand = radicand.checked_mul(n.to_u64()?)?;
+    } else {
+        return None;
+    }
+    Some((square, radicand))
+}
+
+fn rational_sqrt(q: &BigRational) -> Option<Real> {
+    if q.is_negative() {
+        return None;
+    }
+    if q.is_zero() {
+        return Some(Real::zero());
+    }
+    // √(a/b) = √(ab) / b
+    let (square, radicand) = square_free_part(&(q.numer() * q.denom()))?;
+    Some(Real::from_term(
+        radicand,
+        BigRational::new(square, q.denom().clone()),
+    ))
+}
+
+impl fmt::Display for Real {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        for (i, (&radicand, q)) in self.terms.iter().enumerate() {
+            if i > 0 {
+                write!(f, " + ")?;
+            }
+            match radicand {
+                1 => write!(f, "{q}")?,
+                _ => write!(f, "{q}*sqrt({radicand})")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Add for &Real {
+    type Output = Real;
+
+    fn add(self, rhs: &Real) -> Real {
+        let mut res = self.clone();
+        for (&r, q) in &rhs.terms {
+            res.add_term(r, q.clone());
+        }
+        res
+    }
+}
+
+impl Sub for &Real {
+    type Output = Real;
+
+    fn sub(self, rhs: &Real) -> Real {
+        self + &-rhs
+    }
+}
+
+impl Neg for &Real {
+    type Output = Real;
+
+    fn neg(self) -> Real {
+        let terms = self.terms.iter().map(|(&r, q)| (r, -q)).collect();
+        Real { terms }
+    }
+}
+
+/// # Panics
+///
+/// Panics if a radicand of the product overflows, see [`Real::checked_mul`].
+impl Mul for &Real {
+    type Output = Real;
+
+    fn mul(self, rhs: &Real) -> Real {
+        self.checked_mul(rhs).expect("radicand overflow")
+    }
+}
+
+impl Div for &Real {
+    type Output = Real;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: &Real) -> Real {
+        self * &rhs.inverse()
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+macro_rules! forward_owned_binop {
+    ($($trait:ident $method:ident),*) => {$(
+        impl $trait for Real {
+            type Output = Real;
+
+            fn $method(self, rhs: Real) -> Real {
+                (&self).$method(&rhs)
+            }
+        }
+
+        impl $trait<&Real> for Real {
+            type Output = Real;
+
+            fn $method(self, rhs: &Real) -> Real {
+                (&self).$method(rhs)
+            }
+        }
+    )*};
+}
+
+forward_owned_binop!(Add add, Sub sub, Mul mul, Div div);
+
+impl Neg for Real {
+    type Output = Real;
+
+    fn neg(self) -> Real {
+        -&self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt() {
+        let sqrt2 = Real::from_integer(2).sqrt().unwrap();
+        let sqrt3 = Real::from_integer(3).sqrt().unwrap();
+
+        // √(5 + 2√6) = √2 + √3
+        let x = Real::from_integer(5) + &Real::from_integer(2) * &(&sqrt2 * &sqrt3);
+        assert_eq!(x.sqrt(), Some(&sqrt2 + &sqrt3));
+
+        // √(12/50) = √6 / 5
+        let q = Real::from_rational(BigRational::new(12.into(), 50.into()));
+        let root = q.sqrt().unwrap();
+        assert_eq!(&root * &root, q);
+        assert!((root.to_f64() - 6f64.sqrt() / 5.0).abs() < 1e-12);
+
+        // √(1 + √2) can not be denested
+        assert_eq!((Real::one() + sqrt2).sqrt(), None);
+        assert_eq!(Real::from_integer(-1).sqrt(), None);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let sqrt2 = Real::from_integer(2).sqrt().unwrap();
+        let sqrt5 = Real::from_integer(5).sqrt().unwrap();
+        let x = Real::one() + &sqrt2 + &sqrt5 + &sqrt2 * &sqrt5;
+        assert_eq!(&x * &x.inverse(), Real::one());
+
+        // two primes near 2³² are not factored, so the product can not be inverted
+        let p = Real::from_integer(4294967291).sqrt().unwrap();
+        let q = Real::from_integer(4294967279).sqrt().unwrap();
+        assert_eq!(p.checked_mul(&q).unwrap().checked_inverse(), None);
+        assert!(
+            "1/(sqrt(4294967291)*sqrt(4294967279))"
+                .parse::<Real>()
+                .is_err()
+        );
+        assert_eq!(p.checked_inverse().map(|x| &x * &p), Some(Real::one()));
+    }
+}
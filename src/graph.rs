@@ -1,9 +1,14 @@
+pub mod clique;
+pub mod fractional;
 pub mod hash;
+pub mod iso;
+pub mod spectral;
+pub mod theta;
 
 use crate::Node;
-use crate::constants::EPS;
-use approx::AbsDiffEq;
-use nalgebra::Point3;
+use crate::exact::{ExactPoint, Real};
+use crate::spatial::SpatialHash;
+use nalgebra::Point;
 
 pub trait Graph {
     fn size(&self) -> usize;
@@ -11,6 +16,15 @@ pub trait Graph {
     fn edges(&self) -> Vec<(Node, Node)>;
 }
 
+/// Dense adjacency matrix of a graph, for constant time edge lookups.
+pub(crate) fn adjacency<G: Graph>(graph: &G) -> Vec<Vec<bool>> {
+    let mut adj = vec![vec![false; graph.size()]; graph.size()];
+    for (from, to) in graph.edges() {
+        adj[from][to] = true;
+    }
+    adj
+}
+
 pub struct VecVecGraph {
     size: usize,
     edges: Vec<Vec<Node>>,
@@ -29,13 +43,27 @@ impl VecVecGraph {
         self.edges[to].push(from);
     }
 
-    pub fn from_points(points: &[Point3<f64>], dist: f64) -> Self {
+    pub fn from_points<const D: usize>(points: &[Point<f64, D>], dist: f64) -> Self {
+        let mut graph = VecVecGraph::new(points.len());
+        for (i, j) in SpatialHash::new(points, dist).pairs_at_distance(dist) {
+            graph.add_edge(i, j);
+        }
+
+        graph
+    }
+
+    /// Builds the graph of exact points at squared distance exactly `dist_sq`.
+    ///
+    /// Candidate pairs are found on float approximations of the points, then every edge is
+    /// confirmed with exact arithmetic.
+    pub fn from_exact_points(points: &[ExactPoint], dist_sq: &Real) -> Self {
+        let approx: Vec<_> = points.iter().map(ExactPoint::to_point).collect();
+        let dist = dist_sq.to_f64().sqrt();
+
         let mut graph = VecVecGraph::new(points.len());
-        for i in 0..points.len() {
-            for j in 0..i {
-                if (points[i] - points[j]).norm().abs_diff_eq(&dist, EPS) {
-                    graph.add_edge(i, j);
-                }
+        for (i, j) in SpatialHash::new(&approx, dist).pairs_at_distance(dist) {
+            if points[i].sub(&points[j]).norm_squared() == *dist_sq {
+                graph.add_edge(i, j);
             }
         }
 
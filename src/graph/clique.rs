@@ -0,0 +1,221 @@
+use crate::Node;
+use crate::graph::{Graph, adjacency};
+
+/// Counts the cliques with `size` nodes, e.g. triangles for `size = 3`.
+///
+/// Every clique is enumerated once, by extending it only with nodes larger than the ones it
+/// already contains.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::graph::VecVecGraph;
+/// # use ml_cnp::graph::clique::count_cliques;
+///
+/// let mut k4 = VecVecGraph::new(4);
+/// for i in 0..4 {
+///     for j in 0..i {
+///         k4.add_edge(i, j);
+///     }
+/// }
+/// assert_eq!(count_cliques(&k4, 3), 4);
+/// assert_eq!(count_cliques(&k4, 4), 1);
+/// ```
+pub fn count_cliques<G: Graph>(graph: &G, size: usize) -> usize {
+    if size == 0 {
+        return 1;
+    }
+    let adj = adjacency(graph);
+    let candidates: Vec<Node> = (0..graph.size()).collect();
+    count_extensions(&adj, &candidates, size)
+}
+
+/// Counts the cliques with `size` nodes among `candidates`, which are all adjacent to the
+/// clique built so far.
+fn count_extensions(adj: &[Vec<bool>], candidates: &[Node], size: usize) -> usize {
+    if size == 1 {
+        return candidates.len();
+    }
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| {
+            let next: Vec<Node> = candidates[i + 1..]
+                .iter()
+                .cloned()
+                .filter(|&w| adj[v][w])
+                .collect();
+            count_extensions(adj, &next, size - 1)
+        })
+        .sum()
+}
+
+/// Sorts `candidates` into color classes greedily, with nodes in the order given. Returns the
+/// candidates class by class, with the number of the class of every node, starting at `1`.
+/// A clique among the first `i` returned nodes has at most `colors[i - 1]` nodes.
+fn color_sort(adj: &[Vec<bool>], candidates: &[Node]) -> (Vec<Node>, Vec<usize>) {
+    let mut classes: Vec<Vec<Node>> = vec![];
+    for &v in candidates {
+        match classes
+            .iter_mut()
+            .find(|class| class.iter().all(|&u| !adj[u][v]))
+        {
+            Some(class) => class.push(v),
+            None => classes.push(vec![v]),
+        }
+    }
+
+    let mut order = vec![];
+    let mut colors = vec![];
+    for (k, class) in classes.into_iter().enumerate() {
+        colors.extend(std::iter::repeat_n(k + 1, class.len()));
+        order.extend(class);
+    }
+    (order, colors)
+}
+
+fn expand_clique(
+    adj: &[Vec<bool>],
+    candidates: &[Node],
+    current: &mut Vec<Node>,
+    best: &mut Vec<Node>,
+) {
+    let (order, colors) = color_sort(adj, candidates);
+    for i in (0..order.len()).rev() {
+        if current.len() + colors[i] <= best.len() {
+            return;
+        }
+        let v = order[i];
+        let next: Vec<Node> = order[..i].iter().cloned().filter(|&u| adj[v][u]).collect();
+        current.push(v);
+        if next.is_empty() {
+            if current.len() > best.len() {
+                *best = current.clone();
+            }
+        } else {
+            expand_clique(adj, &next, current, best);
+        }
+        current.pop();
+    }
+}
+
+fn max_clique_in(adj: &[Vec<bool>]) -> Vec<Node> {
+    let mut candidates: Vec<Node> = (0..adj.len()).collect();
+    let degree = |v: Node| adj[v].iter().filter(|&&e| e).count();
+    candidates.sort_by_key(|&v| std::cmp::Reverse(degree(v)));
+
+    let mut best = vec![];
+    expand_clique(adj, &candidates, &mut vec![], &mut best);
+    best.sort();
+    best
+}
+
+/// Finds a clique with the most nodes, by branch and bound.
+///
+/// This is the MCQ algorithm of Tomita and Seki: the candidates are greedily colored, and a
+/// branch is cut as soon as the clique built so far plus the number of colors of the
+/// remaining candidates can not beat the best clique found.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::build_graph_from_str;
+/// # use ml_cnp::graph::clique::max_clique;
+///
+/// // a 5-cycle with a chord has a triangle
+/// let graph = build_graph_from_str("5\n0 1\n1 2\n2 3\n3 4\n4 0\n0 2").unwrap();
+/// assert_eq!(max_clique(graph.as_ref()), vec![0, 1, 2]);
+/// ```
+pub fn max_clique<G: Graph>(graph: &G) -> Vec<Node> {
+    max_clique_in(&adjacency(graph))
+}
+
+/// Finds an independent set with the most nodes, as a maximum clique of the complement.
+pub fn max_independent_set<G: Graph>(graph: &G) -> Vec<Node> {
+    let mut adj = adjacency(graph);
+    for (i, row) in adj.iter_mut().enumerate() {
+        for (j, edge) in row.iter_mut().enumerate() {
+            *edge = i != j && !*edge;
+        }
+    }
+    max_clique_in(&adj)
+}
+
+/// The largest number of pairwise non-adjacent nodes.
+pub fn independence_number<G: Graph>(graph: &G) -> usize {
+    max_independent_set(graph).len()
+}
+
+/// The lower bound `χ ≥ n / α` on the chromatic number, as every color class is an
+/// independent set.
+pub fn independence_bound<G: Graph>(graph: &G) -> usize {
+    let alpha = independence_number(graph);
+    if alpha == 0 {
+        0
+    } else {
+        graph.size().div_ceil(alpha)
+    }
+}
+
+/// Finds an independent set of maximum total weight, and its weight, by branch and bound.
+///
+/// The weighted analog of [`max_clique`] on the complement: the candidates are greedily
+/// covered by cliques, each of which contains at most one node of an independent set, so the
+/// heaviest node of every clique bounds what the remaining candidates can add. Nodes of
+/// non-positive weight are never taken.
+pub fn max_weight_independent_set<G: Graph>(graph: &G, weights: &[f64]) -> (Vec<Node>, f64) {
+    let adj = adjacency(graph);
+    let mut candidates: Vec<Node> = (0..graph.size()).filter(|&v| weights[v] > 0.0).collect();
+    candidates.sort_by(|&a, &b| weights[b].total_cmp(&weights[a]));
+
+    let mut best = (vec![], 0.0);
+    expand_independent(&adj, weights, &candidates, &mut vec![], 0.0, &mut best);
+    best.0.sort();
+    best
+}
+
+fn expand_independent(
+    adj: &[Vec<bool>],
+    weights: &[f64],
+    candidates: &[Node],
+    current: &mut Vec<Node>,
+    weight: f64,
+    best: &mut (Vec<Node>, f64),
+) {
+    // greedy clique cover
+    let mut cliques: Vec<Vec<Node>> = vec![];
+    for &v in candidates {
+        match cliques
+            .iter_mut()
+            .find(|clique| clique.iter().all(|&u| adj[u][v]))
+        {
+            Some(clique) => clique.push(v),
+            None => cliques.push(vec![v]),
+        }
+    }
+    let mut order = vec![];
+    let mut bounds = vec![];
+    let mut bound = 0.0;
+    for clique in cliques {
+        bound += clique.iter().map(|&u| weights[u]).fold(0.0, f64::max);
+        bounds.extend(std::iter::repeat_n(bound, clique.len()));
+        order.extend(clique);
+    }
+
+    for i in (0..order.len()).rev() {
+        if weight + bounds[i] <= best.1 {
+            return;
+        }
+        let v = order[i];
+        let next: Vec<Node> = order[..i].iter().cloned().filter(|&u| !adj[v][u]).collect();
+        current.push(v);
+        let with_v = weight + weights[v];
+        if with_v > best.1 {
+            *best = (current.clone(), with_v);
+        }
+        if !next.is_empty() {
+            expand_independent(adj, weights, &next, current, with_v, best);
+        }
+        current.pop();
+    }
+}
@@ -0,0 +1,185 @@
+use crate::Node;
+use crate::algo::dsatur;
+use crate::graph::clique::max_weight_independent_set;
+use crate::graph::{Graph, adjacency};
+use crate::optim::{LinearProgram, LpError};
+use nalgebra::{DMatrix, DVector};
+
+/// An independent set must weigh more than `1 + TOLERANCE` to enter the program.
+const TOLERANCE: f64 = 1e-7;
+
+/// An optimal fractional coloring, see [`fractional_chromatic`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FractionalColoring {
+    /// The fractional chromatic number.
+    pub value: f64,
+    /// Independent sets with their positive weights, which cover every node with total weight
+    /// at least `1`.
+    pub sets: Vec<(Vec<Node>, f64)>,
+    /// Node weights of the dual program: every independent set weighs at most `1`, and the
+    /// weights sum to [`FractionalColoring::value`].
+    pub node_weights: Vec<f64>,
+}
+
+/// Computes the fractional chromatic number of a graph by column generation.
+///
+/// The fractional chromatic number is the optimum of the linear program
+///
+/// ```text
+/// minimize Σ x_S  subject to  Σ_{S ∋ v} x_S ≥ 1 for every node v,  x ≥ 0,
+/// ```
+///
+/// with a variable for every independent set `S`. Rather than listing all of them, the dual
+/// program `maximize Σ y_v subject to Σ_{v ∈ S} y_v ≤ 1` is solved with the independent sets
+/// found so far, starting with the color classes of [`dsatur`]. A heaviest independent set for
+/// the dual weights `y` is then searched: if it weighs more than `1`, it is added, otherwise `y`
+/// is optimal for all independent sets.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::build_graph_from_str;
+/// # use ml_cnp::graph::fractional::fractional_chromatic;
+///
+/// // the 5-cycle has fractional chromatic number 5/2
+/// let cycle = build_graph_from_str("5\n0 1\n1 2\n2 3\n3 4\n4 0").unwrap();
+/// let coloring = fractional_chromatic(cycle.as_ref()).unwrap();
+/// assert!((coloring.value - 2.5).abs() < 1e-9);
+/// ```
+pub fn fractional_chromatic<G: Graph>(graph: &G) -> Result<FractionalColoring, LpError> {
+    let size = graph.size();
+    let adj = adjacency(graph);
+
+    let colors = dsatur(graph);
+    let mut sets: Vec<Vec<Node>> = vec![vec![]; colors.iter().map(|&c| c + 1).max().unwrap_or(0)];
+    for (node, &color) in colors.iter().enumerate() {
+        sets[color].push(node);
+    }
+
+    loop {
+        let constraints = DMatrix::from_fn(sets.len(), size, |i, v| {
+            if sets[i].contains(&v) { 1.0 } else { 0.0 }
+        });
+        let lp = LinearProgram::new(
+            DVector::from_element(size, 1.0),
+            constraints,
+            DVector::from_element(sets.len(), 1.0),
+        );
+        let solution = lp.solve()?;
+        let weights: Vec<f64> = solution.x.iter().cloned().collect();
+
+        match heaviest_independent_set(graph, &adj, &weights) {
+            (set, weight) if weight > 1.0 + TOLERANCE => sets.push(set),
+            _ => {
+                let sets = sets
+                    .into_iter()
+                    .zip(solution.duals.iter())
+                    .filter(|&(_, &x)| x > TOLERANCE)
+                    .map(|(set, &x)| (set, x))
+                    .collect();
+                return Ok(FractionalColoring {
+                    value: solution.value,
+                    sets,
+                    node_weights: weights,
+                });
+            }
+        }
+    }
+}
+
+/// An independent set of maximum total weight, and its weight.
+///
+/// A greedy set is tried first, as any set weighing more than `1` is enough to make progress,
+/// before the exact [`max_weight_independent_set`].
+fn heaviest_independent_set<G: Graph>(
+    graph: &G,
+    adj: &[Vec<bool>],
+    weights: &[f64],
+) -> (Vec<Node>, f64) {
+    let mut order: Vec<Node> = (0..weights.len())
+        .filter(|&v| weights[v] > TOLERANCE)
+        .collect();
+    order.sort_by(|&a, &b| weights[b].total_cmp(&weights[a]));
+
+    let mut greedy = vec![];
+    for &v in &order {
+        if greedy.iter().all(|&u: &Node| !adj[u][v]) {
+            greedy.push(v);
+        }
+    }
+    let greedy_weight = greedy.iter().map(|&v| weights[v]).sum::<f64>();
+    if greedy_weight > 1.0 + TOLERANCE {
+        return (greedy, greedy_weight);
+    }
+
+    max_weight_independent_set(graph, weights)
+}
+
+/// Node weights certifying a lower bound on the chromatic number, see [`independence_ratio`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndependenceRatio {
+    pub weights: Vec<f64>,
+    /// The sum of the weights.
+    pub total: f64,
+    /// An independent set of maximum weight, checked exactly for the weights found.
+    pub heaviest_set: Vec<Node>,
+    pub heaviest_weight: f64,
+    /// `total / heaviest_weight`, a lower bound on the chromatic number.
+    pub ratio: f64,
+}
+
+impl IndependenceRatio {
+    /// Whether the weights prove that `colors` colors are not enough.
+    pub fn forces(&self, colors: usize) -> bool {
+        self.ratio > colors as f64 + TOLERANCE
+    }
+
+    /// How much the ratio must still grow to prove that `colors` colors are not enough, or a
+    /// negative number if it already does.
+    pub fn margin(&self, colors: usize) -> f64 {
+        colors as f64 - self.ratio
+    }
+}
+
+/// Finds node weights maximizing the ratio of the total weight to the weight of a heaviest
+/// independent set.
+///
+/// A color class is an independent set, so `k` colors cover a total weight of at most
+/// `k` times the heaviest independent set, and the ratio is a lower bound on the chromatic
+/// number. This is the weighted argument of the proofs that the plane is not 4-colorable:
+/// the best weights are the dual weights of [`fractional_chromatic`], and the best ratio is
+/// the fractional chromatic number. The heaviest independent set is recomputed for these
+/// weights by [`max_weight_independent_set`], so the ratio does not depend on the accuracy of
+/// the linear program.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::graph::VecVecGraph;
+/// # use ml_cnp::graph::fractional::independence_ratio;
+/// # use ml_cnp::search::moser_spindle;
+///
+/// let spindle = VecVecGraph::from_points(&moser_spindle(), 1.0);
+/// let ratio = independence_ratio(&spindle).unwrap();
+/// assert!((ratio.ratio - 3.5).abs() < 1e-9);
+/// assert!(ratio.forces(3));
+/// assert!(!ratio.forces(4));
+/// ```
+pub fn independence_ratio<G: Graph>(graph: &G) -> Result<IndependenceRatio, LpError> {
+    let weights = fractional_chromatic(graph)?.node_weights;
+    let total = weights.iter().sum();
+    let (heaviest_set, heaviest_weight) = max_weight_independent_set(graph, &weights);
+    let ratio = if heaviest_weight > 0.0 {
+        total / heaviest_weight
+    } else {
+        0.0
+    };
+
+    Ok(IndependenceRatio {
+        weights,
+        total,
+        heaviest_set,
+        heaviest_weight,
+        ratio,
+    })
+}
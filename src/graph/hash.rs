@@ -1,7 +1,7 @@
 use crate::Node;
 use crate::constants::WL_TEST_ROUNDS;
-use crate::graph::Graph;
-use nohash::IntMap;
+use crate::graph::{Graph, adjacency};
+use std::collections::HashMap;
 use xxhash_rust::xxh3::xxh3_64;
 
 /// A trait for a hashable graph.
@@ -12,33 +12,297 @@ pub trait Hashable<T> {
 
 impl<T: Graph> Hashable<u64> for T {
     fn hash(&self) -> u64 {
-        let mut hashes = Vec::new();
-        let mut labels: IntMap<Node, u64> = (0..self.size())
-            .map(|node| (node, self.neighbors(node).len() as u64))
+        WLHasher::default().hash(self)
+    }
+}
+
+/// How many refinement rounds a [`WLHasher`] runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounds {
+    /// Run exactly this many rounds.
+    Fixed(usize),
+    /// Run until the partition into color classes stops getting finer.
+    UntilStable,
+}
+
+/// A configurable Weisfeiler-Lehman graph hasher.
+///
+/// The default hasher runs [`WL_TEST_ROUNDS`] rounds of classic color refinement (1-WL) seeded
+/// with node degrees, which is what [`Hashable::hash`] uses. More rounds, initial vertex labels,
+/// edge labels and higher dimensional variants make the hash more discriminating, at the cost
+/// of speed: k-WL refines colors of all `n^k` tuples of nodes.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::graph::VecVecGraph;
+/// # use ml_cnp::graph::hash::{Rounds, WLHasher};
+///
+/// // C6 and two triangles can not be told apart by 1-WL, but 2-WL sees the triangles
+/// let mut cycle = VecVecGraph::new(6);
+/// let mut triangles = VecVecGraph::new(6);
+/// for i in 0..6 {
+///     cycle.add_edge(i, (i + 1) % 6);
+///     triangles.add_edge(i, i / 3 * 3 + (i + 1) % 3);
+/// }
+///
+/// let hasher = WLHasher::new(Rounds::UntilStable);
+/// assert_eq!(hasher.hash(&cycle), hasher.hash(&triangles));
+///
+/// let hasher = hasher.with_dimension(2);
+/// assert_ne!(hasher.hash(&cycle), hasher.hash(&triangles));
+/// ```
+#[derive(Debug, Clone)]
+pub struct WLHasher {
+    rounds: Rounds,
+    dimension: usize,
+    vertex_labels: Option<Vec<u64>>,
+    edge_labels: HashMap<(Node, Node), u64>,
+}
+
+impl Default for WLHasher {
+    fn default() -> Self {
+        Self::new(Rounds::Fixed(WL_TEST_ROUNDS))
+    }
+}
+
+fn hash_u64s(values: &[u64]) -> u64 {
+    let bytes: Vec<u8> = values.iter().flat_map(|&x| x.to_le_bytes()).collect();
+    xxh3_64(&bytes)
+}
+
+fn class_count(labels: &[u64]) -> usize {
+    let mut labels = labels.to_vec();
+    labels.sort();
+    labels.dedup();
+    labels.len()
+}
+
+impl WLHasher {
+    pub fn new(rounds: Rounds) -> Self {
+        Self {
+            rounds,
+            dimension: 1,
+            vertex_labels: None,
+            edge_labels: HashMap::new(),
+        }
+    }
+
+    /// Use the folklore k-WL test, refining colors of node k-tuples, instead of color
+    /// refinement. Dimension 2 is as strong as classic 3-WL and already counts triangles.
+    pub fn with_dimension(mut self, dimension: usize) -> Self {
+        assert!(dimension >= 1, "WL dimension must be at least 1");
+        self.dimension = dimension;
+        self
+    }
+
+    /// Seed every node with an extra label, e.g. the seed point it originates from.
+    ///
+    /// # Panics
+    ///
+    /// Hashing panics if there is not exactly one label per node of the graph.
+    pub fn with_vertex_labels(mut self, labels: Vec<u64>) -> Self {
+        self.vertex_labels = Some(labels);
+        self
+    }
+
+    /// Attach a label to edges. Edges are undirected, so `(a, b)` and `(b, a)` are the same
+    /// key. Edges without a label get label `0`.
+    pub fn with_edge_labels(
+        mut self,
+        labels: impl IntoIterator<Item = ((Node, Node), u64)>,
+    ) -> Self {
+        self.edge_labels = labels
+            .into_iter()
+            .map(|((a, b), label)| ((a.min(b), a.max(b)), label))
             .collect();
+        self
+    }
 
-        for _ in 0..WL_TEST_ROUNDS {
-            let mut new_labels = IntMap::default();
-            for node in 0..self.size() {
-                let mut new_label: Vec<u64> = self
+    fn edge_label(&self, a: Node, b: Node) -> u64 {
+        self.edge_labels
+            .get(&(a.min(b), a.max(b)))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn initial_labels<G: Graph>(&self, graph: &G) -> Vec<u64> {
+        (0..graph.size())
+            .map(|node| {
+                let degree = graph.neighbors(node).len() as u64;
+                match &self.vertex_labels {
+                    Some(labels) => hash_u64s(&[degree, labels[node]]),
+                    None => degree,
+                }
+            })
+            .collect()
+    }
+
+    /// Runs one round of color refinement.
+    fn refine<G: Graph>(&self, graph: &G, labels: &[u64]) -> Vec<u64> {
+        (0..graph.size())
+            .map(|node| {
+                let mut new_label: Vec<u64> = graph
                     .neighbors(node)
                     .iter()
-                    .map(|neighbor| labels.get(neighbor).cloned().unwrap_or_default())
-                    .chain([labels.get(&node).cloned().unwrap_or_default()])
+                    .map(|&neighbor| {
+                        if self.edge_labels.is_empty() {
+                            labels[neighbor]
+                        } else {
+                            hash_u64s(&[labels[neighbor], self.edge_label(node, neighbor)])
+                        }
+                    })
+                    .chain([labels[node]])
                     .collect();
                 new_label.sort();
-                // transform new label to Vec<u8> and hash it
-                let new_label: Vec<u8> = new_label.iter().flat_map(|&x| x.to_le_bytes()).collect();
-                new_labels.insert(node, xxh3_64(&new_label));
+                hash_u64s(&new_label)
+            })
+            .collect()
+    }
+
+    /// Initial colors of node tuples for k-WL: the isomorphism type of the induced, labelled
+    /// subgraph on the tuple.
+    fn initial_tuple_labels<G: Graph>(&self, graph: &G, tuples: &[Vec<Node>]) -> Vec<u64> {
+        let adj = adjacency(graph);
+        let nodes = self.initial_labels(graph);
+        tuples
+            .iter()
+            .map(|tuple| {
+                let mut atoms: Vec<u64> = tuple.iter().map(|&v| nodes[v]).collect();
+                for a in 0..tuple.len() {
+                    for b in a + 1..tuple.len() {
+                        let (u, v) = (tuple[a], tuple[b]);
+                        let edge = if adj[u][v] {
+                            1 + self.edge_label(u, v)
+                        } else {
+                            0
+                        };
+                        atoms.extend([(u == v) as u64, edge]);
+                    }
+                }
+                hash_u64s(&atoms)
+            })
+            .collect()
+    }
+
+    /// Runs one round of folklore k-WL refinement over all tuples, indexed in base `n`.
+    fn refine_tuples(&self, size: usize, tuples: &[Vec<Node>], labels: &[u64]) -> Vec<u64> {
+        let index = |tuple: &[Node]| tuple.iter().fold(0, |acc, &v| acc * size + v);
+        tuples
+            .iter()
+            .map(|tuple| {
+                let mut neighborhood: Vec<u64> = (0..size)
+                    .map(|w| {
+                        let substituted: Vec<u64> = (0..tuple.len())
+                            .map(|i| {
+                                let mut other = tuple.clone();
+                                other[i] = w;
+                                labels[index(&other)]
+                            })
+                            .collect();
+                        hash_u64s(&substituted)
+                    })
+                    .collect();
+                neighborhood.sort();
+                neighborhood.push(labels[index(tuple)]);
+                hash_u64s(&neighborhood)
+            })
+            .collect()
+    }
+
+    /// Refines the labels of `count` objects, feeding the sorted labels of every round to
+    /// `record`, and returns the final labels.
+    fn run(
+        &self,
+        count: usize,
+        mut labels: Vec<u64>,
+        refine: impl Fn(&[u64]) -> Vec<u64>,
+        mut record: impl FnMut(&[u64]),
+    ) -> Vec<u64> {
+        let max_rounds = match self.rounds {
+            Rounds::Fixed(rounds) => rounds,
+            // the partition can get finer at most `count` times
+            Rounds::UntilStable => count + 1,
+        };
+
+        // the number of classes of the last round, only needed to detect stability
+        let mut classes = (self.rounds == Rounds::UntilStable).then(|| class_count(&labels));
+        for _ in 0..max_rounds {
+            labels = refine(&labels);
+            record(&labels);
+            if let Some(previous) = classes {
+                let current = class_count(&labels);
+                if current == previous {
+                    break;
+                }
+                classes = Some(current);
             }
+        }
+        labels
+    }
 
-            labels = new_labels;
-            let mut values: Vec<_> = labels.values().cloned().collect();
-            values.sort();
-            let values: Vec<u8> = values.iter().flat_map(|&x| x.to_le_bytes()).collect();
-            hashes.extend_from_slice(&values);
+    fn run_graph<G: Graph>(&self, graph: &G, record: impl FnMut(&[u64])) -> Vec<u64> {
+        if let Some(labels) = &self.vertex_labels {
+            assert_eq!(
+                labels.len(),
+                graph.size(),
+                "expected a vertex label per node"
+            );
+        }
+        if self.dimension == 1 {
+            return self.run(
+                graph.size(),
+                self.initial_labels(graph),
+                |labels| self.refine(graph, labels),
+                record,
+            );
         }
 
+        let size = graph.size();
+        let tuples = all_tuples(size, self.dimension);
+        let labels = self.run(
+            tuples.len(),
+            self.initial_tuple_labels(graph, &tuples),
+            |labels| self.refine_tuples(size, &tuples, labels),
+            record,
+        );
+        // the color of a node is the color of its diagonal tuple
+        let step: usize = (0..self.dimension).map(|i| size.pow(i as u32)).sum();
+        (0..size).map(|node| labels[node * step]).collect()
+    }
+
+    /// Returns the final label of every node.
+    ///
+    /// Isomorphic graphs produce the same multiset of labels, and an isomorphism
+    /// can only map a node to a node with the same label.
+    pub fn labels<G: Graph>(&self, graph: &G) -> Vec<u64> {
+        self.run_graph(graph, |_| {})
+    }
+
+    /// Returns a hash of the graph, combining the label multisets of every round.
+    pub fn hash<G: Graph>(&self, graph: &G) -> u64 {
+        let mut hashes = Vec::new();
+        self.run_graph(graph, |labels| {
+            let mut values = labels.to_vec();
+            values.sort();
+            hashes.extend(values.iter().flat_map(|&x| x.to_le_bytes()));
+        });
         xxh3_64(&hashes)
     }
 }
+
+/// All `size^k` tuples of nodes, in lexicographic order.
+fn all_tuples(size: usize, k: usize) -> Vec<Vec<Node>> {
+    (0..k).fold(vec![vec![]], |tuples, _| {
+        tuples
+            .into_iter()
+            .flat_map(|tuple| {
+                (0..size).map(move |v| {
+                    let mut tuple = tuple.clone();
+                    tuple.push(v);
+                    tuple
+                })
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,107 @@
+use crate::Node;
+use crate::graph::hash::WLHasher;
+use crate::graph::{Graph, adjacency};
+use std::collections::HashMap;
+
+struct Matcher {
+    adj_a: Vec<Vec<bool>>,
+    adj_b: Vec<Vec<bool>>,
+    labels_a: Vec<u64>,
+    labels_b: Vec<u64>,
+    /// Nodes of `a` in the order they are matched.
+    order: Vec<Node>,
+    /// `mapping[u]` is the node of `b` that `u` is currently mapped to.
+    mapping: Vec<Option<Node>>,
+    used: Vec<bool>,
+}
+
+impl Matcher {
+    /// Check that mapping `u` to `v` preserves adjacency with all nodes matched so far.
+    fn consistent(&self, u: Node, v: Node, depth: usize) -> bool {
+        self.order[..depth].iter().all(|&w| {
+            let image = self.mapping[w].unwrap();
+            self.adj_a[u][w] == self.adj_b[v][image]
+        }) && self.adj_a[u][u] == self.adj_b[v][v]
+    }
+
+    fn search(&mut self, depth: usize) -> bool {
+        if depth == self.order.len() {
+            return true;
+        }
+
+        let u = self.order[depth];
+        for v in 0..self.labels_b.len() {
+            if self.used[v] || self.labels_b[v] != self.labels_a[u] || !self.consistent(u, v, depth)
+            {
+                continue;
+            }
+
+            self.mapping[u] = Some(v);
+            self.used[v] = true;
+            if self.search(depth + 1) {
+                return true;
+            }
+            self.mapping[u] = None;
+            self.used[v] = false;
+        }
+
+        false
+    }
+}
+
+/// Exact isomorphism test between two graphs.
+///
+/// Nodes are first partitioned by their Weisfeiler-Lehman labels, then a backtracking search
+/// looks for an adjacency preserving bijection that respects the partition. Rare label classes
+/// are matched first to keep the search tree small.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::graph::VecVecGraph;
+/// # use ml_cnp::graph::iso::is_isomorphic;
+///
+/// let mut a = VecVecGraph::new(3);
+/// a.add_edge(0, 1);
+/// a.add_edge(1, 2);
+/// let mut b = VecVecGraph::new(3);
+/// b.add_edge(2, 0);
+/// b.add_edge(0, 1);
+///
+/// assert!(is_isomorphic(&a, &b));
+/// ```
+pub fn is_isomorphic<G1: Graph, G2: Graph>(a: &G1, b: &G2) -> bool {
+    if a.size() != b.size() || a.edges().len() != b.edges().len() {
+        return false;
+    }
+
+    let hasher = WLHasher::default();
+    let labels_a = hasher.labels(a);
+    let labels_b = hasher.labels(b);
+
+    let mut sorted_a = labels_a.clone();
+    let mut sorted_b = labels_b.clone();
+    sorted_a.sort();
+    sorted_b.sort();
+    if sorted_a != sorted_b {
+        return false;
+    }
+
+    let mut class_size: HashMap<u64, usize> = HashMap::new();
+    for &label in &labels_a {
+        *class_size.entry(label).or_default() += 1;
+    }
+    let mut order: Vec<Node> = (0..a.size()).collect();
+    order.sort_by_key(|&node| (class_size[&labels_a[node]], labels_a[node]));
+
+    let mut matcher = Matcher {
+        adj_a: adjacency(a),
+        adj_b: adjacency(b),
+        labels_a,
+        labels_b,
+        order,
+        mapping: vec![None; a.size()],
+        used: vec![false; b.size()],
+    };
+    matcher.search(0)
+}
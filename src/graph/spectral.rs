@@ -0,0 +1,136 @@
+use crate::graph::Graph;
+use nalgebra::{DMatrix, SymmetricEigen};
+
+/// Eigenvalues closer to zero than this are counted as zero, e.g. by the inertia bound.
+const TOLERANCE: f64 = 1e-9;
+
+/// The adjacency matrix of a graph.
+pub fn adjacency_matrix<G: Graph>(graph: &G) -> DMatrix<f64> {
+    let mut matrix = DMatrix::zeros(graph.size(), graph.size());
+    for (from, to) in graph.edges() {
+        matrix[(from, to)] = 1.0;
+    }
+    matrix
+}
+
+/// The Laplacian matrix `D - A` of a graph, where `D` is the diagonal matrix of degrees.
+pub fn laplacian_matrix<G: Graph>(graph: &G) -> DMatrix<f64> {
+    let mut matrix = -adjacency_matrix(graph);
+    for node in 0..graph.size() {
+        matrix[(node, node)] = graph.neighbors(node).len() as f64;
+    }
+    matrix
+}
+
+fn sorted_eigenvalues(matrix: DMatrix<f64>) -> Vec<f64> {
+    let mut eigenvalues: Vec<_> = SymmetricEigen::new(matrix)
+        .eigenvalues
+        .iter()
+        .cloned()
+        .collect();
+    eigenvalues.sort_by(|a, b| b.total_cmp(a));
+    eigenvalues
+}
+
+/// Adjacency and Laplacian spectra of a graph, and the bounds on the chromatic number they
+/// give.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::graph::VecVecGraph;
+/// # use ml_cnp::graph::spectral::Spectrum;
+///
+/// // K4 has eigenvalues 3, -1, -1, -1, which make every bound tight
+/// let mut k4 = VecVecGraph::new(4);
+/// for i in 0..4 {
+///     for j in 0..i {
+///         k4.add_edge(i, j);
+///     }
+/// }
+///
+/// let spectrum = Spectrum::new(&k4);
+/// assert_eq!(spectrum.lower_bound(), 4);
+/// assert_eq!(spectrum.upper_bound(), 4);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum {
+    /// Eigenvalues of the adjacency matrix, in decreasing order.
+    pub adjacency: Vec<f64>,
+    /// Eigenvalues of the Laplacian matrix, in decreasing order.
+    pub laplacian: Vec<f64>,
+}
+
+impl Spectrum {
+    pub fn new<G: Graph>(graph: &G) -> Self {
+        Self {
+            adjacency: sorted_eigenvalues(adjacency_matrix(graph)),
+            laplacian: sorted_eigenvalues(laplacian_matrix(graph)),
+        }
+    }
+
+    /// The largest adjacency eigenvalue, `0` for the empty graph.
+    pub fn max_eigenvalue(&self) -> f64 {
+        self.adjacency.first().cloned().unwrap_or(0.0)
+    }
+
+    /// The smallest adjacency eigenvalue, `0` for the empty graph.
+    pub fn min_eigenvalue(&self) -> f64 {
+        self.adjacency.last().cloned().unwrap_or(0.0)
+    }
+
+    /// Hoffman's ratio bound `χ ≥ 1 - λ_max / λ_min`.
+    pub fn hoffman_bound(&self) -> f64 {
+        if self.min_eigenvalue() < -TOLERANCE {
+            1.0 - self.max_eigenvalue() / self.min_eigenvalue()
+        } else {
+            1.0
+        }
+    }
+
+    /// The inertia bound `χ ≥ 1 + max(n⁺ / n⁻, n⁻ / n⁺)` of Elphick and Wocjan, where `n⁺` and
+    /// `n⁻` are the numbers of positive and negative adjacency eigenvalues.
+    pub fn inertia_bound(&self) -> f64 {
+        let positive = self.adjacency.iter().filter(|&&x| x > TOLERANCE).count() as f64;
+        let negative = self.adjacency.iter().filter(|&&x| x < -TOLERANCE).count() as f64;
+        if positive == 0.0 || negative == 0.0 {
+            return 1.0;
+        }
+        1.0 + (positive / negative).max(negative / positive)
+    }
+
+    /// Kolotilina's bound `χ ≥ 1 + λ_max / (μ_max - λ_max)`, where `μ_max` is the largest
+    /// Laplacian eigenvalue.
+    pub fn laplacian_bound(&self) -> f64 {
+        let mu = self.laplacian.first().cloned().unwrap_or(0.0);
+        let lambda = self.max_eigenvalue();
+        if mu - lambda > TOLERANCE {
+            1.0 + lambda / (mu - lambda)
+        } else {
+            1.0
+        }
+    }
+
+    /// Wilf's bound `χ ≤ 1 + λ_max`.
+    pub fn wilf_bound(&self) -> f64 {
+        1.0 + self.max_eigenvalue()
+    }
+
+    /// The best of the lower bounds, before rounding. It is finer than [`Spectrum::lower_bound`]
+    /// to rank graphs.
+    pub fn best_lower_bound(&self) -> f64 {
+        self.hoffman_bound()
+            .max(self.inertia_bound())
+            .max(self.laplacian_bound())
+    }
+
+    /// A lower bound on the chromatic number.
+    pub fn lower_bound(&self) -> usize {
+        (self.best_lower_bound() - TOLERANCE).ceil() as usize
+    }
+
+    /// An upper bound on the chromatic number.
+    pub fn upper_bound(&self) -> usize {
+        (self.wilf_bound() + TOLERANCE).floor() as usize
+    }
+}
@@ -0,0 +1,60 @@
+use crate::graph::{Graph, adjacency};
+use crate::optim::{Sdp, SdpConstraint};
+use nalgebra::DMatrix;
+
+/// An approximation of the Lovász theta function, see [`lovasz_theta_complement`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theta {
+    pub value: f64,
+    /// The largest constraint violation of the matrix achieving [`Theta::value`].
+    pub violation: f64,
+}
+
+/// Approximates the Lovász theta function of the complement of a graph, which lies between
+/// its clique number and its chromatic number, and is computed in polynomial time.
+///
+/// It is the optimum of the semidefinite program
+///
+/// ```text
+/// maximize ⟨J, X⟩  subject to  tr X = 1,  X_ij = 0 for non-adjacent i ≠ j,  X ⪰ 0,
+/// ```
+///
+/// solved with [`Sdp`]. The value is that of an approximately feasible matrix, so it can
+/// slightly exceed the true theta when [`Theta::violation`] is not negligible.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::build_graph_from_str;
+/// # use ml_cnp::graph::theta::lovasz_theta_complement;
+///
+/// // the 5-cycle is self-complementary, with theta √5
+/// let cycle = build_graph_from_str("5\n0 1\n1 2\n2 3\n3 4\n4 0").unwrap();
+/// let theta = lovasz_theta_complement(cycle.as_ref());
+/// assert!((theta.value - 5f64.sqrt()).abs() < 1e-4);
+/// ```
+pub fn lovasz_theta_complement<G: Graph>(graph: &G) -> Theta {
+    let size = graph.size();
+    let adj = adjacency(graph);
+
+    let mut constraints = vec![SdpConstraint {
+        entries: (0..size).map(|i| (i, i, 1.0)).collect(),
+        rhs: 1.0,
+    }];
+    for (i, row) in adj.iter().enumerate() {
+        for (j, &edge) in row.iter().enumerate().skip(i + 1) {
+            if !edge {
+                constraints.push(SdpConstraint {
+                    entries: vec![(i, j, 1.0)],
+                    rhs: 0.0,
+                });
+            }
+        }
+    }
+
+    let solution = Sdp::new(DMatrix::from_element(size, size, 1.0), constraints).solve();
+    Theta {
+        value: solution.value,
+        violation: solution.violation,
+    }
+}
@@ -0,0 +1,72 @@
+mod checkpoint;
+mod graph;
+mod layer;
+mod points;
+
+pub use checkpoint::{
+    CHECKPOINT_VERSION, Checkpoint, SearchSettings, load_checkpoint, read_checkpoint,
+    save_checkpoint, write_checkpoint,
+};
+pub use graph::{
+    GraphFormat, MAX_NODES, load_graph, parse_graph, parse_graph_as, to_graph6, to_sparse6,
+    write_graph, write_graphml,
+};
+pub use layer::{
+    FORMAT_VERSION, Format, LayerRecord, layer_records, load_layer, read_layer_binary,
+    read_layer_text, save_layer, write_layer_binary, write_layer_text,
+};
+pub use points::{
+    PointFormat, load_points, parse_exact_points, parse_points, points_dimension,
+    write_exact_points, write_points,
+};
+use std::fmt;
+
+/// An error met while reading or writing a file.
+#[derive(Debug)]
+pub enum IoError {
+    Io(std::io::Error),
+    /// The content is malformed.
+    Format {
+        /// 1-based line number of the offending line in a text file, `0` for binary files and
+        /// errors about the whole file.
+        line: usize,
+        message: String,
+    },
+    /// The file was written by a newer version of the format.
+    Version(u32),
+}
+
+impl IoError {
+    pub(crate) fn format(line: usize, message: impl Into<String>) -> Self {
+        IoError::Format {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IoError::Io(err) => write!(f, "{err}"),
+            IoError::Format { line: 0, message } => write!(f, "{message}"),
+            IoError::Format { line, message } => write!(f, "line {line}: {message}"),
+            IoError::Version(version) => write!(f, "unsupported format version {version}"),
+        }
+    }
+}
+
+impl std::error::Error for IoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IoError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for IoError {
+    fn from(err: std::io::Error) -> Self {
+        IoError::Io(err)
+    }
+}
@@ -0,0 +1,307 @@
+use crate::io::IoError;
+use crate::io::layer::{LayerRecord, read_layer_binary, read_u32, read_u64, write_layer_binary};
+use crate::search::{Dedup, LayerReport, Scored, Selection};
+use nalgebra::Point;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// The version written in the header of checkpoint files.
+pub const CHECKPOINT_VERSION: u32 = 2;
+
+/// First bytes of a checkpoint file, followed by the version.
+const CHECKPOINT_MAGIC: &[u8; 8] = b"MLCNPCKP";
+
+/// The settings of a [`crate::search::BeamSearch`] that affect its result, so that a checkpoint
+/// is only resumed by the same search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchSettings {
+    /// The number of depths.
+    pub depth: usize,
+    /// The beam width of every depth, [`None`] to keep every candidate.
+    pub widths: Vec<Option<usize>>,
+    pub dedup: Dedup,
+    /// The names of the move generators.
+    pub moves: Vec<String>,
+    /// The name of the scorer.
+    pub scorer: String,
+    pub selection: Selection,
+}
+
+impl SearchSettings {
+    /// The name of the first setting that differs from `other`, if any.
+    pub fn difference(&self, other: &SearchSettings) -> Option<&'static str> {
+        if self.depth != other.depth {
+            Some("depth")
+        } else if self.widths != other.widths {
+            Some("beam width")
+        } else if self.dedup != other.dedup {
+            Some("deduplication")
+        } else if self.moves != other.moves {
+            Some("set of moves")
+        } else if self.scorer != other.scorer {
+            Some("scorer")
+        } else if self.selection != other.selection {
+            Some("selection")
+        } else {
+            None
+        }
+    }
+}
+
+/// The state of an interrupted layered search, see
+/// [`crate::search::BeamSearch::resume`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint<const D: usize> {
+    /// The distance that makes an edge.
+    pub dist: f64,
+    pub settings: SearchSettings,
+    /// The depth being generated, 1-based. It is past the last depth once the search is done.
+    pub depth: usize,
+    /// Number of point sets of the frontier already expanded at this depth.
+    pub expanded: usize,
+    /// The point sets the current depth is generated from.
+    pub frontier: Vec<LayerRecord<D>>,
+    /// The distinct candidates generated at this depth so far.
+    pub candidates: Vec<LayerRecord<D>>,
+    /// Hashes of every graph met so far, for [`crate::search::Dedup::Global`].
+    pub seen: Vec<u64>,
+    /// Reports of the completed depths.
+    pub layers: Vec<LayerReport<D>>,
+}
+
+fn write_points<W: Write, const D: usize>(
+    writer: &mut W,
+    points: &[Point<f64, D>],
+) -> Result<(), IoError> {
+    writer.write_all(&(points.len() as u64).to_le_bytes())?;
+    for point in points {
+        for x in point.iter() {
+            writer.write_all(&x.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+fn write_string<W: Write>(writer: &mut W, value: &str) -> Result<(), IoError> {
+    writer.write_all(&(value.len() as u32).to_le_bytes())?;
+    writer.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, IoError> {
+    let length = read_u32(reader)? as u64;
+    let mut bytes = vec![];
+    reader.by_ref().take(length).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != length {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    String::from_utf8(bytes).map_err(|_| IoError::format(0, "invalid name"))
+}
+
+fn write_settings<W: Write>(writer: &mut W, settings: &SearchSettings) -> Result<(), IoError> {
+    writer.write_all(&(settings.depth as u64).to_le_bytes())?;
+    writer.write_all(&(settings.widths.len() as u64).to_le_bytes())?;
+    for width in &settings.widths {
+        let width = width.map_or(u64::MAX, |w| w as u64);
+        writer.write_all(&width.to_le_bytes())?;
+    }
+    let dedup: u32 = match settings.dedup {
+        Dedup::Layer => 0,
+        Dedup::Checked => 1,
+        Dedup::Global => 2,
+    };
+    writer.write_all(&dedup.to_le_bytes())?;
+    writer.write_all(&(settings.moves.len() as u32).to_le_bytes())?;
+    for name in &settings.moves {
+        write_string(writer, name)?;
+    }
+    write_string(writer, &settings.scorer)?;
+    let (selection, max_similarity): (u32, f64) = match settings.selection {
+        Selection::TopK => (0, 0.0),
+        Selection::Diverse { max_similarity } => (1, max_similarity),
+    };
+    writer.write_all(&selection.to_le_bytes())?;
+    writer.write_all(&max_similarity.to_le_bytes())?;
+    Ok(())
+}
+
+fn read_settings<R: Read>(reader: &mut R) -> Result<SearchSettings, IoError> {
+    let depth = read_u64(reader)? as usize;
+    let count = read_u64(reader)?;
+    let mut widths = Vec::with_capacity((count as usize).min(1 << 16));
+    for _ in 0..count {
+        widths.push(match read_u64(reader)? {
+            u64::MAX => None,
+            width => Some(width as usize),
+        });
+    }
+    let dedup = match read_u32(reader)? {
+        0 => Dedup::Layer,
+        1 => Dedup::Checked,
+        2 => Dedup::Global,
+        dedup => return Err(IoError::format(0, format!("invalid deduplication {dedup}"))),
+    };
+    let moves = (0..read_u32(reader)?)
+        .map(|_| read_string(reader))
+        .collect::<Result<_, _>>()?;
+    let scorer = read_string(reader)?;
+    let selection = read_u32(reader)?;
+    let max_similarity = f64::from_bits(read_u64(reader)?);
+    let selection = match selection {
+        0 => Selection::TopK,
+        1 => Selection::Diverse { max_similarity },
+        selection => return Err(IoError::format(0, format!("invalid selection {selection}"))),
+    };
+    Ok(SearchSettings {
+        depth,
+        widths,
+        dedup,
+        moves,
+        scorer,
+        selection,
+    })
+}
+
+fn read_points<R: Read, const D: usize>(reader: &mut R) -> Result<Vec<Point<f64, D>>, IoError> {
+    let size = read_u64(reader)? as usize;
+    let mut points = Vec::with_capacity(size.min(1 << 16));
+    for _ in 0..size {
+        let mut coords = [0.0; D];
+        for x in &mut coords {
+            *x = f64::from_bits(read_u64(reader)?);
+        }
+        points.push(Point::from(coords));
+    }
+    Ok(points)
+}
+
+/// Writes a checkpoint in a little-endian binary format: the magic `MLCNPCKP`, the version as
+/// `u32`, the distance as `f64`, the [`SearchSettings`], the depth and number of expanded point
+/// sets as `u64`, the frontier and the candidates as layer files (see
+/// [`crate::io::Format::Binary`]), the seen hashes and the layer reports, each preceded by their
+/// number.
+pub fn write_checkpoint<W: Write, const D: usize>(
+    writer: &mut W,
+    checkpoint: &Checkpoint<D>,
+) -> Result<(), IoError> {
+    writer.write_all(CHECKPOINT_MAGIC)?;
+    writer.write_all(&CHECKPOINT_VERSION.to_le_bytes())?;
+    writer.write_all(&checkpoint.dist.to_le_bytes())?;
+    write_settings(writer, &checkpoint.settings)?;
+    writer.write_all(&(checkpoint.depth as u64).to_le_bytes())?;
+    writer.write_all(&(checkpoint.expanded as u64).to_le_bytes())?;
+    write_layer_binary(writer, &checkpoint.frontier)?;
+    write_layer_binary(writer, &checkpoint.candidates)?;
+
+    writer.write_all(&(checkpoint.seen.len() as u64).to_le_bytes())?;
+    for hash in &checkpoint.seen {
+        writer.write_all(&hash.to_le_bytes())?;
+    }
+
+    writer.write_all(&(checkpoint.layers.len() as u64).to_le_bytes())?;
+    for layer in &checkpoint.layers {
+        for n in [layer.depth, layer.generated, layer.kept, layer.best.len()] {
+            writer.write_all(&(n as u64).to_le_bytes())?;
+        }
+        for scored in &layer.best {
+            writer.write_all(&scored.score.to_le_bytes())?;
+            write_points(writer, &scored.points)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a checkpoint written by [`write_checkpoint`].
+pub fn read_checkpoint<const D: usize, R: Read>(reader: &mut R) -> Result<Checkpoint<D>, IoError> {
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != CHECKPOINT_MAGIC {
+        return Err(IoError::format(0, "not a checkpoint file"));
+    }
+    let version = read_u32(reader)?;
+    // earlier checkpoints do not record the settings of the search
+    if version != CHECKPOINT_VERSION {
+        return Err(IoError::Version(version));
+    }
+
+    let dist = f64::from_bits(read_u64(reader)?);
+    let settings = read_settings(reader)?;
+    let depth = read_u64(reader)? as usize;
+    let expanded = read_u64(reader)? as usize;
+    let frontier = read_layer_binary(reader)?;
+    let candidates = read_layer_binary(reader)?;
+    if expanded > frontier.len() {
+        return Err(IoError::format(
+            0,
+            format!("{expanded} point sets expanded out of {}", frontier.len()),
+        ));
+    }
+
+    let count = read_u64(reader)?;
+    let seen = (0..count)
+        .map(|_| read_u64(reader))
+        .collect::<Result<_, _>>()?;
+
+    let count = read_u64(reader)?;
+    let mut layers = vec![];
+    for _ in 0..count {
+        let depth = read_u64(reader)? as usize;
+        let generated = read_u64(reader)? as usize;
+        let kept = read_u64(reader)? as usize;
+        let mut best = vec![];
+        for _ in 0..read_u64(reader)? {
+            let score = f64::from_bits(read_u64(reader)?);
+            let points = read_points(reader)?;
+            best.push(Scored { score, points });
+        }
+        layers.push(LayerReport {
+            depth,
+            generated,
+            kept,
+            best,
+        });
+    }
+
+    Ok(Checkpoint {
+        dist,
+        settings,
+        depth,
+        expanded,
+        frontier,
+        candidates,
+        seen,
+        layers,
+    })
+}
+
+/// Saves a checkpoint atomically: it is written to `<path>.tmp` first, then renamed over
+/// `path`, so a crash while saving leaves the previous checkpoint intact.
+pub fn save_checkpoint<const D: usize>(
+    path: impl AsRef<Path>,
+    checkpoint: &Checkpoint<D>,
+) -> Result<(), IoError> {
+    let path = path.as_ref();
+    let mut tmp = OsString::from(path.as_os_str());
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let mut writer = BufWriter::new(File::create(&tmp)?);
+    write_checkpoint(&mut writer, checkpoint)?;
+    let file = writer.into_inner().map_err(|err| err.into_error())?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Loads a checkpoint saved by [`save_checkpoint`], or [`None`] if there is no file at `path`.
+pub fn load_checkpoint<const D: usize>(
+    path: impl AsRef<Path>,
+) -> Result<Option<Checkpoint<D>>, IoError> {
+    match std::fs::read(path) {
+        Ok(bytes) => read_checkpoint(&mut bytes.as_slice()).map(Some),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
@@ -0,0 +1,510 @@
+use crate::graph::{Graph, VecVecGraph, adjacency};
+use crate::io::IoError;
+use crate::{Color, Node};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+
+/// Optional first characters of a graph6 line.
+const GRAPH6_HEADER: &str = ">>graph6<<";
+
+/// The largest number of nodes read from a file.
+pub const MAX_NODES: usize = 1 << 20;
+
+/// A text format for graphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// The number of nodes on the first line, then an edge `from to` per line, with nodes
+    /// numbered from `0`.
+    Native,
+    /// The DIMACS format of graph coloring instances (`.col` files): comment lines start with
+    /// `c`, the line `p edge <nodes> <edges>` comes first, then an edge `e <from> <to>` per
+    /// line, with nodes numbered from `1`.
+    Dimacs,
+    /// An edge `from to` per line, with nodes numbered from `0`, and as many nodes as the
+    /// largest index needs.
+    EdgeList,
+    /// The compact format of nauty, a single line of printable characters holding the upper
+    /// triangle of the adjacency matrix, optionally preceded by `>>graph6<<`.
+    Graph6,
+}
+
+impl GraphFormat {
+    /// Guesses the format of a description: native if its first line is a single number,
+    /// graph6 if it is a single word of other characters, DIMACS if it has a `p` line, an edge
+    /// list otherwise.
+    pub fn detect(desc: &str) -> Self {
+        let mut lines = content_lines(desc).map(|(_, line)| line);
+        match lines.next() {
+            Some(line) if line.starts_with(GRAPH6_HEADER) => GraphFormat::Graph6,
+            Some(line) if line.split_whitespace().count() == 1 => {
+                if line.bytes().all(|b| b.is_ascii_digit()) {
+                    GraphFormat::Native
+                } else {
+                    GraphFormat::Graph6
+                }
+            }
+            _ if content_lines(desc).any(|(_, line)| line.starts_with("p ")) => GraphFormat::Dimacs,
+            _ => GraphFormat::EdgeList,
+        }
+    }
+}
+
+/// The numbered lines of `desc` that are neither blank nor comments. Comments start with `#`,
+/// `%`, or `c` followed by a space, as in DIMACS files.
+fn content_lines(desc: &str) -> impl Iterator<Item = (usize, &str)> {
+    desc.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| {
+            !(line.is_empty()
+                || line.starts_with('#')
+                || line.starts_with('%')
+                || *line == "c"
+                || line.starts_with("c "))
+        })
+}
+
+fn parse_index(value: &str, line: usize) -> Result<usize, IoError> {
+    value
+        .parse()
+        .map_err(|_| IoError::format(line, format!("invalid node `{value}`")))
+}
+
+/// Collects the edges of a graph, checking that they join two distinct existing nodes.
+/// Repeated edges are only added once.
+struct EdgeSet {
+    graph: VecVecGraph,
+    edges: HashSet<(Node, Node)>,
+}
+
+impl EdgeSet {
+    /// Fails on sizes above [`MAX_NODES`], which are rather corrupt input than graphs to
+    /// allocate.
+    fn new(size: usize, line: usize) -> Result<Self, IoError> {
+        if size > MAX_NODES {
+            return Err(IoError::format(
+                line,
+                format!("{size} nodes is more than the supported {MAX_NODES}"),
+            ));
+        }
+        Ok(Self {
+            graph: VecVecGraph::new(size),
+            edges: HashSet::new(),
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.graph.size()
+    }
+
+    fn add(&mut self, from: Node, to: Node, line: usize) -> Result<(), IoError> {
+        let size = self.graph.size();
+        for node in [from, to] {
+            if node >= size {
+                return Err(IoError::format(
+                    line,
+                    format!("node {node} out of range for {size} nodes"),
+                ));
+            }
+        }
+        if from == to {
+            return Err(IoError::format(line, format!("loop on node {from}")));
+        }
+        if self.edges.insert((from.min(to), from.max(to))) {
+            self.graph.add_edge(from, to);
+        }
+        Ok(())
+    }
+}
+
+/// Parses a graph in the given format.
+pub fn parse_graph_as(desc: &str, format: GraphFormat) -> Result<VecVecGraph, IoError> {
+    match format {
+        GraphFormat::Native => parse_native(desc),
+        GraphFormat::Dimacs => parse_dimacs(desc),
+        GraphFormat::EdgeList => parse_edge_list(desc),
+        GraphFormat::Graph6 => parse_graph6(desc),
+    }
+}
+
+/// Parses a graph, guessing its format with [`GraphFormat::detect`].
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::graph::Graph;
+/// # use ml_cnp::io::parse_graph;
+///
+/// let triangle = parse_graph("c a triangle\np edge 3 3\ne 1 2\ne 2 3\ne 3 1").unwrap();
+/// assert_eq!(triangle.size(), 3);
+/// assert_eq!(triangle.neighbors(0).len(), 2);
+///
+/// let err = parse_graph("3\n0 1\n1 3").err().unwrap();
+/// assert_eq!(err.to_string(), "line 3: node 3 out of range for 3 nodes");
+/// ```
+pub fn parse_graph(desc: &str) -> Result<VecVecGraph, IoError> {
+    parse_graph_as(desc, GraphFormat::detect(desc))
+}
+
+/// Reads a graph from a file, guessing its format.
+pub fn load_graph(path: impl AsRef<Path>) -> Result<VecVecGraph, IoError> {
+    parse_graph(&std::fs::read_to_string(path)?)
+}
+
+fn parse_edge(line: &str, line_no: usize) -> Result<(Node, Node), IoError> {
+    match line.split_whitespace().collect::<Vec<_>>()[..] {
+        [from, to] => Ok((parse_index(from, line_no)?, parse_index(to, line_no)?)),
+        _ => Err(IoError::format(line_no, "expected an edge `<from> <to>`")),
+    }
+}
+
+fn parse_native(desc: &str) -> Result<VecVecGraph, IoError> {
+    let mut lines = content_lines(desc);
+    let (line_no, line) = lines
+        .next()
+        .ok_or_else(|| IoError::format(0, "missing number of nodes"))?;
+    let size = line
+        .parse()
+        .map_err(|_| IoError::format(line_no, format!("invalid number of nodes `{line}`")))?;
+
+    let mut edges = EdgeSet::new(size, line_no)?;
+    for (line_no, line) in lines {
+        let (from, to) = parse_edge(line, line_no)?;
+        edges.add(from, to, line_no)?;
+    }
+    Ok(edges.graph)
+}
+
+fn parse_dimacs(desc: &str) -> Result<VecVecGraph, IoError> {
+    let mut edges: Option<EdgeSet> = None;
+    for (line_no, line) in content_lines(desc) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields[..] {
+            ["p", _, size, _] if edges.is_none() => {
+                let size = size.parse().map_err(|_| {
+                    IoError::format(line_no, format!("invalid number of nodes `{size}`"))
+                })?;
+                edges = Some(EdgeSet::new(size, line_no)?);
+            }
+            ["p", ..] if edges.is_some() => {
+                return Err(IoError::format(line_no, "repeated problem line"));
+            }
+            ["p", ..] => {
+                return Err(IoError::format(
+                    line_no,
+                    "expected `p edge <nodes> <edges>`",
+                ));
+            }
+            ["e", from, to] => {
+                let edges = edges.as_mut().ok_or_else(|| {
+                    IoError::format(line_no, "edge before the problem line `p edge`")
+                })?;
+                let (from, to) = (parse_index(from, line_no)?, parse_index(to, line_no)?);
+                if from == 0 || to == 0 {
+                    return Err(IoError::format(line_no, "DIMACS nodes are numbered from 1"));
+                }
+                // ranges are checked before renumbering, so errors show the nodes as written
+                let size = edges.size();
+                if let Some(node) = [from, to].into_iter().find(|&node| node > size) {
+                    return Err(IoError::format(
+                        line_no,
+                        format!("node {node} out of range for {size} nodes"),
+                    ));
+                }
+                edges.add(from - 1, to - 1, line_no)?;
+            }
+            _ => return Err(IoError::format(line_no, "expected `e <from> <to>`")),
+        }
+    }
+    edges
+        .map(|edges| edges.graph)
+        .ok_or_else(|| IoError::format(0, "missing problem line `p edge`"))
+}
+
+fn parse_edge_list(desc: &str) -> Result<VecVecGraph, IoError> {
+    let mut list = vec![];
+    for (line_no, line) in content_lines(desc) {
+        list.push((parse_edge(line, line_no)?, line_no));
+    }
+    let (size, line_no) = list
+        .iter()
+        .map(|&((from, to), line_no)| (from.max(to).saturating_add(1), line_no))
+        .max()
+        .unwrap_or((0, 0));
+
+    let mut edges = EdgeSet::new(size, line_no)?;
+    for ((from, to), line_no) in list {
+        edges.add(from, to, line_no)?;
+    }
+    Ok(edges.graph)
+}
+
+/// Writes a graph in the given format, which [`parse_graph_as`] reads back.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::io::{GraphFormat, parse_graph, write_graph};
+/// # use ml_cnp::graph::{Graph, VecVecGraph};
+///
+/// let mut path = VecVecGraph::new(3);
+/// path.add_edge(0, 1);
+/// path.add_edge(1, 2);
+///
+/// let mut dimacs = vec![];
+/// write_graph(&mut dimacs, &path, GraphFormat::Dimacs).unwrap();
+/// let dimacs = String::from_utf8(dimacs).unwrap();
+/// assert_eq!(dimacs, "p edge 3 2\ne 1 2\ne 2 3\n");
+/// assert_eq!(parse_graph(&dimacs).unwrap().edges(), path.edges());
+///
+/// let mut graph6 = vec![];
+/// write_graph(&mut graph6, &path, GraphFormat::Graph6).unwrap();
+/// assert_eq!(graph6, b"Bg\n");
+/// ```
+pub fn write_graph<W: Write, G: Graph>(
+    writer: &mut W,
+    graph: &G,
+    format: GraphFormat,
+) -> Result<(), IoError> {
+    let edges = undirected_edges(graph);
+    match format {
+        GraphFormat::Native => {
+            writeln!(writer, "{}", graph.size())?;
+            for (from, to) in edges {
+                writeln!(writer, "{from} {to}")?;
+            }
+        }
+        GraphFormat::Dimacs => {
+            writeln!(writer, "p edge {} {}", graph.size(), edges.len())?;
+            for (from, to) in edges {
+                writeln!(writer, "e {} {}", from + 1, to + 1)?;
+            }
+        }
+        GraphFormat::EdgeList => {
+            for (from, to) in edges {
+                writeln!(writer, "{from} {to}")?;
+            }
+        }
+        GraphFormat::Graph6 => writeln!(writer, "{}", to_graph6(graph))?,
+    }
+    Ok(())
+}
+
+/// Every edge once, as `(from, to)` with `from < to`, in increasing order.
+fn undirected_edges<G: Graph>(graph: &G) -> Vec<(Node, Node)> {
+    let mut edges: Vec<_> = graph
+        .edges()
+        .into_iter()
+        .filter(|&(from, to)| from < to)
+        .collect();
+    edges.sort();
+    edges.dedup();
+    edges
+}
+
+/// The graph6 and sparse6 encoding of the number of nodes.
+fn encode_size(size: usize, out: &mut Vec<u8>) {
+    if size < 63 {
+        out.push(size as u8 + 63);
+    } else if size <= 258047 {
+        // larger sizes would start with `~` again, which marks the 8 byte form
+        out.push(126);
+        out.extend((0..3).rev().map(|i| ((size >> (6 * i)) & 63) as u8 + 63));
+    } else {
+        out.extend([126, 126]);
+        out.extend((0..6).rev().map(|i| ((size >> (6 * i)) & 63) as u8 + 63));
+    }
+}
+
+/// Packs bits into printable characters, 6 bits each, padding the last one with `pad`.
+fn encode_bits(bits: &[bool], pad: bool, out: &mut Vec<u8>) {
+    for chunk in bits.chunks(6) {
+        let mut byte = 0;
+        for i in 0..6 {
+            byte = byte << 1 | u8::from(chunk.get(i).copied().unwrap_or(pad));
+        }
+        out.push(byte + 63);
+    }
+}
+
+/// Encodes a graph in graph6, without header.
+pub fn to_graph6<G: Graph>(graph: &G) -> String {
+    let size = graph.size();
+    let adj = adjacency(graph);
+    let mut out = vec![];
+    encode_size(size, &mut out);
+
+    // the upper triangle, column by column
+    let bits: Vec<bool> = (1..size)
+        .flat_map(|j| (0..j).map(move |i| (i, j)))
+        .map(|(i, j)| adj[i][j])
+        .collect();
+    encode_bits(&bits, false, &mut out);
+    String::from_utf8(out).unwrap()
+}
+
+/// Encodes a graph in sparse6, the format of nauty for sparse graphs, which lists the edges
+/// rather than the adjacency matrix.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::io::to_sparse6;
+/// # use ml_cnp::graph::VecVecGraph;
+///
+/// let mut graph = VecVecGraph::new(7);
+/// for (from, to) in [(0, 1), (0, 2), (1, 2), (5, 6)] {
+///     graph.add_edge(from, to);
+/// }
+/// assert_eq!(to_sparse6(&graph), ":Fa@x^");
+/// ```
+pub fn to_sparse6<G: Graph>(graph: &G) -> String {
+    let size = graph.size();
+    // bits needed for a node
+    let mut k = 1;
+    while 1 << k < size {
+        k += 1;
+    }
+    let encode =
+        |x: usize, bits: &mut Vec<bool>| bits.extend((0..k).rev().map(|i| x >> i & 1 == 1));
+
+    let mut edges: Vec<_> = undirected_edges(graph)
+        .into_iter()
+        .map(|(from, to)| (to, from))
+        .collect();
+    edges.sort();
+
+    let mut bits = vec![];
+    let mut current = 0;
+    for (v, u) in edges {
+        if v == current {
+            bits.push(false);
+        } else if v == current + 1 {
+            current = v;
+            bits.push(true);
+        } else {
+            current = v;
+            bits.push(true);
+            encode(v, &mut bits);
+            bits.push(false);
+        }
+        encode(u, &mut bits);
+    }
+    // padding with ones must not read as an edge to node `size - 1`
+    let padding = (6 - bits.len() % 6) % 6;
+    if k < 6 && size == 1 << k && padding >= k && current + 1 < size {
+        bits.push(false);
+    }
+
+    let mut out = vec![b':'];
+    encode_size(size, &mut out);
+    encode_bits(&bits, true, &mut out);
+    String::from_utf8(out).unwrap()
+}
+
+fn parse_graph6(desc: &str) -> Result<VecVecGraph, IoError> {
+    let mut lines = content_lines(desc);
+    let (line_no, line) = lines
+        .next()
+        .ok_or_else(|| IoError::format(0, "missing graph6 line"))?;
+    if let Some((line_no, _)) = lines.next() {
+        return Err(IoError::format(line_no, "expected a single graph"));
+    }
+
+    let line = line.strip_prefix(GRAPH6_HEADER).unwrap_or(line);
+    let mut data = vec![];
+    for byte in line.bytes() {
+        if !(63..=126).contains(&byte) {
+            return Err(IoError::format(
+                line_no,
+                format!("invalid graph6 character `{}`", byte as char),
+            ));
+        }
+        data.push(byte - 63);
+    }
+
+    let (size, data) = match data[..] {
+        [63, 63, ..] if data.len() >= 8 => (decode_size(&data[2..8]), &data[8..]),
+        [63, ..] if data.len() >= 4 => (decode_size(&data[1..4]), &data[4..]),
+        [n, ..] if n < 63 => (n as usize, &data[1..]),
+        _ => return Err(IoError::format(line_no, "invalid graph6 size")),
+    };
+    if size > MAX_NODES {
+        return Err(IoError::format(
+            line_no,
+            format!("{size} nodes is more than the supported {MAX_NODES}"),
+        ));
+    }
+    let needed = size
+        .checked_mul(size.saturating_sub(1))
+        .map(|bits| (bits / 2).div_ceil(6));
+    if needed != Some(data.len()) {
+        let needed = needed.map_or("too many".to_string(), |n| n.to_string());
+        return Err(IoError::format(
+            line_no,
+            format!(
+                "expected {needed} characters of edges for {size} nodes, found {}",
+                data.len()
+            ),
+        ));
+    }
+
+    let mut graph = VecVecGraph::new(size);
+    let pairs = (1..size).flat_map(|j| (0..j).map(move |i| (i, j)));
+    for (bit, (i, j)) in pairs.enumerate() {
+        if data[bit / 6] >> (5 - bit % 6) & 1 == 1 {
+            graph.add_edge(i, j);
+        }
+    }
+    Ok(graph)
+}
+
+fn decode_size(data: &[u8]) -> usize {
+    data.iter().fold(0, |size, &x| size << 6 | x as usize)
+}
+
+/// Writes a graph in GraphML, the XML format of graph drawing tools, with the color of every
+/// node as a `color` attribute if given. The colors must have one entry per node.
+pub fn write_graphml<W: Write, G: Graph>(
+    writer: &mut W,
+    graph: &G,
+    colors: Option<&[Color]>,
+) -> Result<(), IoError> {
+    if let Some(colors) = colors
+        && colors.len() != graph.size()
+    {
+        return Err(IoError::format(
+            0,
+            format!("{} colors for {} nodes", colors.len(), graph.size()),
+        ));
+    }
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    if colors.is_some() {
+        writeln!(
+            writer,
+            r#"  <key id="color" for="node" attr.name="color" attr.type="int"/>"#
+        )?;
+    }
+    writeln!(writer, r#"  <graph id="G" edgedefault="undirected">"#)?;
+    for node in 0..graph.size() {
+        match colors {
+            Some(colors) => writeln!(
+                writer,
+                r#"    <node id="n{node}"><data key="color">{}</data></node>"#,
+                colors[node]
+            )?,
+            None => writeln!(writer, r#"    <node id="n{node}"/>"#)?,
+        }
+    }
+    for (from, to) in undirected_edges(graph) {
+        writeln!(writer, r#"    <edge source="n{from}" target="n{to}"/>"#)?;
+    }
+    writeln!(writer, "  </graph>")?;
+    writeln!(writer, "</graphml>")?;
+    Ok(())
+}
@@ -0,0 +1,374 @@
+use crate::graph::VecVecGraph;
+use crate::graph::hash::Hashable;
+use crate::io::IoError;
+use nalgebra::Point;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// The version written in the header of layer files. Files of older versions can still be
+/// read.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// First line of a text layer file, followed by the version.
+const TEXT_HEADER: &str = "ml-cnp-layer";
+/// First bytes of a binary layer file, followed by the version.
+const BINARY_MAGIC: &[u8; 8] = b"MLCNPLYR";
+
+/// A point set of a search layer, with where it comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerRecord<const D: usize> {
+    pub id: usize,
+    /// The id of the point set it was generated from, if any.
+    pub parent: Option<usize>,
+    /// The move applied to the parent, such as the name of a [`crate::search::MoveGenerator`]
+    /// or a [`crate::search::AxisMove`]. It must not contain whitespace.
+    pub generator: Option<String>,
+    /// The Weisfeiler-Lehman hash of the unit distance graph.
+    pub hash: u64,
+    pub points: Vec<Point<f64, D>>,
+}
+
+impl<const D: usize> LayerRecord<D> {
+    /// A record without parent, hashing the graph of `points` at distance `dist`.
+    pub fn new(id: usize, points: Vec<Point<f64, D>>, dist: f64) -> Self {
+        let hash = VecVecGraph::from_points(&points, dist).hash();
+        Self {
+            id,
+            parent: None,
+            generator: None,
+            hash,
+            points,
+        }
+    }
+
+    /// Records that the point set was obtained from `parent` by the move `generator`.
+    pub fn with_parent(mut self, parent: usize, generator: &str) -> Self {
+        self.parent = Some(parent);
+        self.generator = Some(generator.to_string());
+        self
+    }
+}
+
+/// Records for a whole layer, numbered from `first_id`, e.g. the output of
+/// [`crate::search::next_layer`].
+pub fn layer_records<const D: usize>(
+    layer: &[Vec<Point<f64, D>>],
+    dist: f64,
+    first_id: usize,
+) -> Vec<LayerRecord<D>> {
+    layer
+        .iter()
+        .enumerate()
+        .map(|(i, points)| LayerRecord::new(first_id + i, points.clone(), dist))
+        .collect()
+}
+
+/// The encoding of a layer file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A readable text format:
+    ///
+    /// ```text
+    /// ml-cnp-layer 1
+    /// dimension 3
+    /// # record <id> <parent> <generator> <hash> <number of points>, `-` for a missing value
+    /// record 0 - - 5f3a09c1d2e4b677 4
+    /// 0 0 0
+    /// ...
+    /// ```
+    ///
+    /// Coordinates are written with the shortest representation that reads back to the same
+    /// value, so nothing is lost.
+    Text,
+    /// A compact little-endian binary format: the magic `MLCNPLYR`, the version and dimension
+    /// as `u32`, the number of records as `u64`, then for every record its id, parent
+    /// (`u64::MAX` for none) and hash as `u64`, the length of the generator name as `u32`
+    /// (`0` for none) followed by the name, the number of points as `u64` and the coordinates
+    /// as `f64`.
+    Binary,
+}
+
+/// The generator name of a record, which must be a non-empty word other than `-` so that
+/// both formats can hold it.
+fn generator_name<const D: usize>(record: &LayerRecord<D>) -> Result<Option<&str>, IoError> {
+    match record.generator.as_deref() {
+        Some(name) if !valid_generator(name) => Err(IoError::format(
+            0,
+            format!("invalid generator name `{name}`"),
+        )),
+        name => Ok(name),
+    }
+}
+
+fn valid_generator(name: &str) -> bool {
+    !name.is_empty() && !name.contains(char::is_whitespace) && name != "-"
+}
+
+/// Writes records in [`Format::Text`].
+pub fn write_layer_text<W: Write, const D: usize>(
+    writer: &mut W,
+    records: &[LayerRecord<D>],
+) -> Result<(), IoError> {
+    writeln!(writer, "{TEXT_HEADER} {FORMAT_VERSION}")?;
+    writeln!(writer, "dimension {D}")?;
+    for record in records {
+        let parent = record.parent.map_or("-".to_string(), |p| p.to_string());
+        let generator = generator_name(record)?.unwrap_or("-");
+        writeln!(
+            writer,
+            "record {} {parent} {generator} {:016x} {}",
+            record.id,
+            record.hash,
+            record.points.len()
+        )?;
+        for point in &record.points {
+            let coords: Vec<String> = point.iter().map(|x| x.to_string()).collect();
+            writeln!(writer, "{}", coords.join(" "))?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads records in [`Format::Text`].
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::io::{LayerRecord, read_layer_text, write_layer_text};
+/// # use nalgebra::Point2;
+///
+/// let points = vec![Point2::new(0.0, 0.0), Point2::new(1.0, 0.0)];
+/// let records = vec![LayerRecord::new(0, points, 1.0)];
+///
+/// let mut text = vec![];
+/// write_layer_text(&mut text, &records).unwrap();
+/// let read = read_layer_text::<2>(std::str::from_utf8(&text).unwrap()).unwrap();
+/// assert_eq!(read, records);
+/// ```
+pub fn read_layer_text<const D: usize>(text: &str) -> Result<Vec<LayerRecord<D>>, IoError> {
+    // skip blank lines and comments, keeping line numbers
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let (line_no, header) = lines
+        .next()
+        .ok_or_else(|| IoError::format(0, "empty layer file"))?;
+    let version = match header.split_whitespace().collect::<Vec<_>>()[..] {
+        [TEXT_HEADER, version] => parse(version, line_no, "version")?,
+        _ => return Err(IoError::format(line_no, "not a layer file")),
+    };
+    if version > FORMAT_VERSION {
+        return Err(IoError::Version(version));
+    }
+
+    let (line_no, line) = lines
+        .next()
+        .ok_or_else(|| IoError::format(0, "missing dimension"))?;
+    let dimension: usize = match line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["dimension", dimension] => parse(dimension, line_no, "dimension")?,
+        _ => return Err(IoError::format(line_no, "expected `dimension <d>`")),
+    };
+    if dimension != D {
+        return Err(IoError::format(
+            line_no,
+            format!("expected dimension {D}, found {dimension}"),
+        ));
+    }
+
+    let mut records = vec![];
+    while let Some((line_no, line)) = lines.next() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let ["record", id, parent, generator, hash, count] = fields[..] else {
+            return Err(IoError::format(
+                line_no,
+                "expected `record <id> <parent> <generator> <hash> <points>`",
+            ));
+        };
+        let id = parse(id, line_no, "id")?;
+        let parent = match parent {
+            "-" => None,
+            parent => Some(parse(parent, line_no, "parent")?),
+        };
+        let generator = match generator {
+            "-" => None,
+            generator => Some(generator.to_string()),
+        };
+        let hash = u64::from_str_radix(hash, 16)
+            .map_err(|_| IoError::format(line_no, format!("invalid hash `{hash}`")))?;
+        let count: usize = parse(count, line_no, "number of points")?;
+
+        let mut points = Vec::with_capacity(count.min(1 << 16));
+        for _ in 0..count {
+            let (line_no, line) = lines
+                .next()
+                .ok_or_else(|| IoError::format(0, format!("record {id} is truncated")))?;
+            let coords = line
+                .split_whitespace()
+                .map(|x| parse(x, line_no, "coordinate"))
+                .collect::<Result<Vec<f64>, _>>()?;
+            if coords.len() != D {
+                return Err(IoError::format(
+                    line_no,
+                    format!("expected {D} coordinates, found {}", coords.len()),
+                ));
+            }
+            points.push(Point::from_slice(&coords));
+        }
+
+        records.push(LayerRecord {
+            id,
+            parent,
+            generator,
+            hash,
+            points,
+        });
+    }
+    Ok(records)
+}
+
+fn parse<T: std::str::FromStr>(value: &str, line: usize, what: &str) -> Result<T, IoError> {
+    value
+        .parse()
+        .map_err(|_| IoError::format(line, format!("invalid {what} `{value}`")))
+}
+
+/// Writes records in [`Format::Binary`].
+pub fn write_layer_binary<W: Write, const D: usize>(
+    writer: &mut W,
+    records: &[LayerRecord<D>],
+) -> Result<(), IoError> {
+    writer.write_all(BINARY_MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&(D as u32).to_le_bytes())?;
+    writer.write_all(&(records.len() as u64).to_le_bytes())?;
+    for record in records {
+        writer.write_all(&(record.id as u64).to_le_bytes())?;
+        let parent = record.parent.map_or(u64::MAX, |p| p as u64);
+        writer.write_all(&parent.to_le_bytes())?;
+        writer.write_all(&record.hash.to_le_bytes())?;
+        let generator = generator_name(record)?.unwrap_or("");
+        writer.write_all(&(generator.len() as u32).to_le_bytes())?;
+        writer.write_all(generator.as_bytes())?;
+        writer.write_all(&(record.points.len() as u64).to_le_bytes())?;
+        for point in &record.points {
+            for x in point.iter() {
+                writer.write_all(&x.to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn read_u32<R: Read>(reader: &mut R) -> Result<u32, IoError> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub(super) fn read_u64<R: Read>(reader: &mut R) -> Result<u64, IoError> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Reads records in [`Format::Binary`].
+pub fn read_layer_binary<const D: usize, R: Read>(
+    reader: &mut R,
+) -> Result<Vec<LayerRecord<D>>, IoError> {
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != BINARY_MAGIC {
+        return Err(IoError::format(0, "not a layer file"));
+    }
+    let version = read_u32(reader)?;
+    if version > FORMAT_VERSION {
+        return Err(IoError::Version(version));
+    }
+    let dimension = read_u32(reader)? as usize;
+    if dimension != D {
+        return Err(IoError::format(
+            0,
+            format!("expected dimension {D}, found {dimension}"),
+        ));
+    }
+
+    let count = read_u64(reader)?;
+    let mut records = vec![];
+    for _ in 0..count {
+        let id = read_u64(reader)? as usize;
+        let parent = match read_u64(reader)? {
+            u64::MAX => None,
+            parent => Some(parent as usize),
+        };
+        let hash = read_u64(reader)?;
+
+        // the length is not trusted to allocate, a corrupt file ends early instead
+        let length = read_u32(reader)? as u64;
+        let mut generator = vec![];
+        reader.by_ref().take(length).read_to_end(&mut generator)?;
+        if generator.len() as u64 != length {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        let generator = match String::from_utf8(generator) {
+            Ok(name) if name.is_empty() => None,
+            Ok(name) if valid_generator(&name) => Some(name),
+            _ => {
+                return Err(IoError::format(
+                    0,
+                    format!("record {id}: invalid generator"),
+                ));
+            }
+        };
+
+        let size = read_u64(reader)? as usize;
+        let mut points = Vec::with_capacity(size.min(1 << 16));
+        for _ in 0..size {
+            let mut coords = [0.0; D];
+            for x in &mut coords {
+                *x = f64::from_bits(read_u64(reader)?);
+            }
+            points.push(Point::from(coords));
+        }
+
+        records.push(LayerRecord {
+            id,
+            parent,
+            generator,
+            hash,
+            points,
+        });
+    }
+    Ok(records)
+}
+
+/// Saves records to a file.
+pub fn save_layer<const D: usize>(
+    path: impl AsRef<Path>,
+    records: &[LayerRecord<D>],
+    format: Format,
+) -> Result<(), IoError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    match format {
+        Format::Text => write_layer_text(&mut writer, records)?,
+        Format::Binary => write_layer_binary(&mut writer, records)?,
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Loads records saved by [`save_layer`], in either format.
+pub fn load_layer<const D: usize>(path: impl AsRef<Path>) -> Result<Vec<LayerRecord<D>>, IoError> {
+    let mut bytes = vec![];
+    BufReader::new(File::open(path)?).read_to_end(&mut bytes)?;
+    if bytes.starts_with(BINARY_MAGIC) {
+        read_layer_binary(&mut bytes.as_slice())
+    } else {
+        let text =
+            std::str::from_utf8(&bytes).map_err(|_| IoError::format(0, "not a layer file"))?;
+        read_layer_text(text)
+    }
+}
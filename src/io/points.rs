@@ -0,0 +1,523 @@
+use crate::exact::{ExactPoint, Real};
+use crate::io::IoError;
+use nalgebra::Point;
+use std::io::Write;
+use std::path::Path;
+
+/// A text format for point sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointFormat {
+    /// A point per line, its coordinates separated by whitespace. Lines starting with `#` are
+    /// comments.
+    Xyz,
+    /// A JSON object `{"dimension": 3, "points": [[0, 0, 0], ...]}`, or just the array of
+    /// points. Coordinates are numbers, or strings holding expressions as in
+    /// [`PointFormat::Symbolic`].
+    Json,
+    /// A point per line, its coordinates separated by commas. Coordinates are exact
+    /// expressions such as `(1+sqrt(3))/2`, see [`Real`]'s `FromStr`. Lines starting with `#`
+    /// are comments.
+    Symbolic,
+}
+
+impl PointFormat {
+    /// Guesses the format of a description: JSON if it starts with `{` or `[`, symbolic if a
+    /// line has a comma, XYZ otherwise.
+    pub fn detect(desc: &str) -> Self {
+        let mut lines = content_lines(desc).map(|(_, line)| line);
+        match lines.next() {
+            Some(line) if line.starts_with('{') || line.starts_with('[') => PointFormat::Json,
+            Some(line) if line.contains(',') => PointFormat::Symbolic,
+            _ => PointFormat::Xyz,
+        }
+    }
+}
+
+fn content_lines(desc: &str) -> impl Iterator<Item = (usize, &str)> {
+    desc.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// A coordinate as written in a file, with the line it is on.
+struct Coordinate<'a> {
+    text: &'a str,
+    line: usize,
+    /// Whether it may be an expression rather than a number.
+    symbolic: bool,
+}
+
+impl Coordinate<'_> {
+    fn to_f64(&self) -> Result<f64, IoError> {
+        match self.text.parse::<f64>() {
+            Ok(x) if x.is_finite() => Ok(x),
+            Ok(_) => Err(IoError::format(
+                self.line,
+                format!("non-finite coordinate `{}`", self.text),
+            )),
+            Err(_) if self.symbolic => Ok(self.to_real()?.to_f64()),
+            Err(_) => Err(IoError::format(
+                self.line,
+                format!("invalid number `{}`", self.text),
+            )),
+        }
+    }
+
+    fn to_real(&self) -> Result<Real, IoError> {
+        self.text.parse().map_err(|err| {
+            IoError::format(
+                self.line,
+                format!("invalid coordinate `{}`: {err}", self.text),
+            )
+        })
+    }
+}
+
+/// Splits a description into points of `D` coordinates each.
+fn coordinates<const D: usize>(
+    desc: &str,
+    format: PointFormat,
+) -> Result<Vec<Vec<Coordinate<'_>>>, IoError> {
+    let points = match format {
+        PointFormat::Xyz => content_lines(desc)
+            .map(|(line, text)| {
+                text.split_whitespace()
+                    .map(|text| Coordinate {
+                        text,
+                        line,
+                        symbolic: false,
+                    })
+                    .collect()
+            })
+            .collect(),
+        PointFormat::Symbolic => content_lines(desc)
+            .map(|(line, text)| {
+                text.split(',')
+                    .map(|text| Coordinate {
+                        text: text.trim(),
+                        line,
+                        symbolic: true,
+                    })
+                    .collect()
+            })
+            .collect(),
+        PointFormat::Json => json_coordinates::<D>(desc)?,
+    };
+
+    for point in &points {
+        if point.len() != D {
+            let line = point.first().map_or(0, |c| c.line);
+            return Err(IoError::format(
+                line,
+                format!("expected {D} coordinates, found {}", point.len()),
+            ));
+        }
+    }
+    Ok(points)
+}
+
+/// Parses points in the given format.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::io::{PointFormat, parse_points};
+///
+/// let triangle = "# an equilateral triangle\n0, 0\n1, 0\n1/2, sqrt(3)/2";
+/// let points = parse_points::<2>(triangle, PointFormat::Symbolic).unwrap();
+/// assert!(((points[2] - points[1]).norm() - 1.0).abs() < 1e-15);
+/// ```
+pub fn parse_points<const D: usize>(
+    desc: &str,
+    format: PointFormat,
+) -> Result<Vec<Point<f64, D>>, IoError> {
+    coordinates::<D>(desc, format)?
+        .iter()
+        .map(|point| {
+            let coords = point
+                .iter()
+                .map(Coordinate::to_f64)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Point::from_slice(&coords))
+        })
+        .collect()
+}
+
+/// The dimension of a point set: the `dimension` of a JSON object, or the number of
+/// coordinates of the first point. The other points are only checked when they are parsed.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::io::{PointFormat, points_dimension};
+///
+/// assert_eq!(points_dimension("# a segment\n0 0\n1 0", PointFormat::Xyz).unwrap(), 2);
+/// assert_eq!(points_dimension("[[0, 0, 0, 0]]", PointFormat::Json).unwrap(), 4);
+/// ```
+pub fn points_dimension(desc: &str, format: PointFormat) -> Result<usize, IoError> {
+    let first = content_lines(desc).next().map(|(_, line)| line);
+    let dimension = match format {
+        PointFormat::Xyz => first.map(|line| line.split_whitespace().count()),
+        PointFormat::Symbolic => first.map(|line| line.split(',').count()),
+        PointFormat::Json => {
+            let document = json_document(desc)?;
+            match (document.dimension, document.points.first()) {
+                (Some((dimension, line)), _) => Some(dimension.parse().map_err(|_| {
+                    IoError::format(line, format!("invalid dimension {dimension}"))
+                })?),
+                (None, Some(Json::Array(coords))) => Some(coords.len()),
+                (None, Some(_)) => {
+                    return Err(IoError::format(
+                        0,
+                        "a point must be an array of coordinates",
+                    ));
+                }
+                (None, None) => None,
+            }
+        }
+    };
+    dimension.ok_or_else(|| IoError::format(0, "no points given"))
+}
+
+/// Parses 3D points with exact coordinates, for
+/// [`crate::graph::VecVecGraph::from_exact_points`].
+pub fn parse_exact_points(desc: &str, format: PointFormat) -> Result<Vec<ExactPoint>, IoError> {
+    coordinates::<3>(desc, format)?
+        .iter()
+        .map(|point| {
+            let [x, y, z] = [&point[0], &point[1], &point[2]].map(Coordinate::to_real);
+            Ok(ExactPoint::new(x?, y?, z?))
+        })
+        .collect()
+}
+
+/// Reads points from a file, guessing its format.
+pub fn load_points<const D: usize>(path: impl AsRef<Path>) -> Result<Vec<Point<f64, D>>, IoError> {
+    let desc = std::fs::read_to_string(path)?;
+    parse_points(&desc, PointFormat::detect(&desc))
+}
+
+/// Writes points in the given format, which [`parse_points`] reads back to the same values.
+/// In [`PointFormat::Symbolic`], coordinates are written as exact fractions.
+pub fn write_points<W: Write, const D: usize>(
+    writer: &mut W,
+    points: &[Point<f64, D>],
+    format: PointFormat,
+) -> Result<(), IoError> {
+    if let Some(x) = points
+        .iter()
+        .flat_map(|p| p.iter())
+        .find(|x| !x.is_finite())
+    {
+        return Err(IoError::format(0, format!("can not write coordinate {x}")));
+    }
+    let rows: Vec<Vec<String>> = points
+        .iter()
+        .map(|p| {
+            p.iter()
+                .map(|&x| match format {
+                    PointFormat::Symbolic => Real::from_f64(x).to_string(),
+                    _ => x.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    write_rows(writer, &rows, D, format, false)
+}
+
+/// Writes exact points in the given format. [`PointFormat::Xyz`] only holds approximations,
+/// and [`PointFormat::Json`] holds the exact coordinates as strings.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::exact::{ExactPoint, Real};
+/// # use ml_cnp::io::{PointFormat, parse_exact_points, write_exact_points};
+///
+/// let half: Real = "1/2".parse().unwrap();
+/// let height: Real = "sqrt(3)/2".parse().unwrap();
+/// let points = vec![ExactPoint::default(), ExactPoint::new(half, height, Real::zero())];
+///
+/// for format in [PointFormat::Json, PointFormat::Symbolic] {
+///     let mut out = vec![];
+///     write_exact_points(&mut out, &points, format).unwrap();
+///     let desc = String::from_utf8(out).unwrap();
+///     assert_eq!(parse_exact_points(&desc, format).unwrap(), points);
+/// }
+/// ```
+pub fn write_exact_points<W: Write>(
+    writer: &mut W,
+    points: &[ExactPoint],
+    format: PointFormat,
+) -> Result<(), IoError> {
+    let rows: Vec<Vec<String>> = points
+        .iter()
+        .map(|p| {
+            p.coords
+                .iter()
+                .map(|x| match format {
+                    PointFormat::Xyz => x.to_f64().to_string(),
+                    _ => x.to_string(),
+                })
+                .collect()
+        })
+        .collect();
+    write_rows(writer, &rows, 3, format, true)
+}
+
+fn write_rows<W: Write>(
+    writer: &mut W,
+    rows: &[Vec<String>],
+    dimension: usize,
+    format: PointFormat,
+    quoted: bool,
+) -> Result<(), IoError> {
+    match format {
+        PointFormat::Xyz => {
+            for row in rows {
+                writeln!(writer, "{}", row.join(" "))?;
+            }
+        }
+        PointFormat::Symbolic => {
+            for row in rows {
+                writeln!(writer, "{}", row.join(", "))?;
+            }
+        }
+        PointFormat::Json => {
+            writeln!(writer, "{{")?;
+            writeln!(writer, "  \"dimension\": {dimension},")?;
+            writeln!(writer, "  \"points\": [")?;
+            for (i, row) in rows.iter().enumerate() {
+                let coords: Vec<String> = if quoted {
+                    row.iter().map(|x| format!("\"{x}\"")).collect()
+                } else {
+                    row.clone()
+                };
+                let comma = if i + 1 < rows.len() { "," } else { "" };
+                writeln!(writer, "    [{}]{comma}", coords.join(", "))?;
+            }
+            writeln!(writer, "  ]")?;
+            writeln!(writer, "}}")?;
+        }
+    }
+    Ok(())
+}
+
+/// The parts of JSON needed for point files. Numbers keep their text, to be read exactly.
+enum Json<'a> {
+    Null,
+    Bool,
+    Number(&'a str, usize),
+    String(&'a str, usize),
+    Array(Vec<Json<'a>>),
+    Object(Vec<(&'a str, Json<'a>)>),
+}
+
+/// The deepest nesting of JSON values, far beyond the three levels of a point file, to keep the
+/// recursion off the end of the stack.
+const MAX_DEPTH: usize = 100;
+
+/// A JSON parser without escapes in strings, which point files do not need.
+struct JsonParser<'a> {
+    text: &'a str,
+    pos: usize,
+    /// The line of `pos`, counted along as the parser moves on.
+    line: usize,
+    /// The number of arrays and objects around `pos`.
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn error(&self, message: &str) -> IoError {
+        IoError::format(self.line, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        let skipped = &rest[..rest.len() - rest.trim_start().len()];
+        self.line += skipped.matches('\n').count();
+        self.pos += skipped.len();
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), IoError> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", c as char)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Json<'a>, IoError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("arrays nested too deeply"));
+        }
+        self.depth += 1;
+        let value = self.nested_value();
+        self.depth -= 1;
+        value
+    }
+
+    fn nested_value(&mut self) -> Result<Json<'a>, IoError> {
+        match self.peek() {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut members = vec![];
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Json::Object(members));
+                }
+                loop {
+                    let Json::String(key, _) = self.value()? else {
+                        return Err(self.error("expected a key"));
+                    };
+                    self.expect(b':')?;
+                    members.push((key, self.value()?));
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Json::Object(members));
+                        }
+                        _ => return Err(self.error("expected `,` or `}`")),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = vec![];
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    match self.peek() {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Json::Array(items));
+                        }
+                        _ => return Err(self.error("expected `,` or `]`")),
+                    }
+                }
+            }
+            Some(b'"') => {
+                let line = self.line;
+                self.pos += 1;
+                let len = self.text[self.pos..]
+                    .find(['"', '\\'])
+                    .ok_or_else(|| self.error("unterminated string"))?;
+                if self.text[self.pos + len..].starts_with('\\') {
+                    return Err(self.error("escapes are not supported"));
+                }
+                let value = &self.text[self.pos..self.pos + len];
+                self.pos += len + 1;
+                self.line += value.matches('\n').count();
+                Ok(Json::String(value, line))
+            }
+            Some(_) => {
+                let rest = &self.text[self.pos..];
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || "+-.".contains(c)))
+                    .unwrap_or(rest.len());
+                let word = &rest[..len];
+                let line = self.line;
+                self.pos += len;
+                match word {
+                    "null" => Ok(Json::Null),
+                    "true" | "false" => Ok(Json::Bool),
+                    "" => Err(self.error("expected a value")),
+                    _ => Ok(Json::Number(word, line)),
+                }
+            }
+            None => Err(self.error("unexpected end of file")),
+        }
+    }
+}
+
+/// A JSON point set, with its declared dimension and the line it is on.
+struct JsonDocument<'a> {
+    dimension: Option<(&'a str, usize)>,
+    points: Vec<Json<'a>>,
+}
+
+fn json_document(desc: &str) -> Result<JsonDocument<'_>, IoError> {
+    let mut parser = JsonParser {
+        text: desc,
+        pos: 0,
+        line: 1,
+        depth: 0,
+    };
+    let value = parser.value()?;
+    if parser.peek().is_some() {
+        return Err(parser.error("unexpected content after the points"));
+    }
+
+    match value {
+        Json::Array(points) => Ok(JsonDocument {
+            dimension: None,
+            points,
+        }),
+        Json::Object(members) => {
+            let (mut points, mut dimension) = (None, None);
+            for (key, value) in members {
+                match (key, value) {
+                    ("points", Json::Array(array)) => points = Some(array),
+                    ("dimension", Json::Number(text, line)) => dimension = Some((text, line)),
+                    ("points" | "dimension", _) => {
+                        return Err(IoError::format(0, format!("invalid `{key}`")));
+                    }
+                    _ => {}
+                }
+            }
+            let points = points.ok_or_else(|| IoError::format(0, "missing `points`"))?;
+            Ok(JsonDocument { dimension, points })
+        }
+        _ => Err(IoError::format(0, "expected an object or an array")),
+    }
+}
+
+fn json_coordinates<const D: usize>(desc: &str) -> Result<Vec<Vec<Coordinate<'_>>>, IoError> {
+    let JsonDocument { dimension, points } = json_document(desc)?;
+    if let Some((dimension, line)) = dimension
+        && dimension != D.to_string()
+    {
+        return Err(IoError::format(
+            line,
+            format!("expected dimension {D}, found {dimension}"),
+        ));
+    }
+
+    points
+        .into_iter()
+        .map(|point| match point {
+            Json::Array(coords) => coords
+                .into_iter()
+                .map(|x| match x {
+                    Json::Number(text, line) => Ok(Coordinate {
+                        text,
+                        line,
+                        symbolic: false,
+                    }),
+                    Json::String(text, line) => Ok(Coordinate {
+                        text,
+                        line,
+                        symbolic: true,
+                    }),
+                    _ => Err(IoError::format(0, "coordinates must be numbers or strings")),
+                })
+                .collect(),
+            _ => Err(IoError::format(
+                0,
+                "a point must be an array of coordinates",
+            )),
+        })
+        .collect()
+}
@@ -0,0 +1,39 @@
+mod features;
+mod gnn;
+
+use crate::algo::{ColorAlgorithm, HeuristicColoring, dsatur_colors};
+use crate::graph::VecVecGraph;
+pub use features::FeatureExtractor;
+pub use gnn::{Gnn, TrainConfig};
+use rayon::prelude::*;
+use std::rc::Rc;
+
+/// A graph labeled with whether it can be colored with a given number of colors.
+pub struct Example {
+    pub graph: VecVecGraph,
+    pub uncolorable: bool,
+}
+
+/// Labels graphs with the exact colorers, as training data for the models of this module.
+///
+/// Graphs that [`crate::algo::dsatur`] colors with `colors` colors are labeled colorable
+/// right away, the others are decided by [`HeuristicColoring`].
+pub fn label_examples(graphs: Vec<VecVecGraph>, colors: usize) -> Vec<Example> {
+    graphs
+        .into_par_iter()
+        .map(|graph| {
+            if dsatur_colors(&graph) <= colors {
+                return Example {
+                    graph,
+                    uncolorable: false,
+                };
+            }
+            let graph = Rc::new(graph);
+            let uncolorable = HeuristicColoring::create(colors, graph.clone())
+                .color()
+                .is_none();
+            let graph = Rc::into_inner(graph).unwrap();
+            Example { graph, uncolorable }
+        })
+        .collect()
+}
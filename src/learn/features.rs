@@ -0,0 +1,180 @@
+use crate::Node;
+use crate::graph::clique::count_cliques;
+use crate::graph::hash::WLHasher;
+use crate::graph::spectral::Spectrum;
+use crate::graph::{Graph, adjacency};
+use nalgebra::{DMatrix, DVector};
+use rayon::prelude::*;
+
+/// Names of the features computed before the WL histogram, in order.
+const SCALAR_FEATURES: [&str; 13] = [
+    "nodes",
+    "edges",
+    "min_degree",
+    "max_degree",
+    "mean_degree",
+    "degree_std",
+    "triangles",
+    "k4s",
+    "max_eigenvalue",
+    "min_eigenvalue",
+    "hoffman_bound",
+    "mean_clustering",
+    "transitivity",
+];
+
+/// Fraction of the pairs of neighbors of `node` that are adjacent.
+pub(crate) fn local_clustering<G: Graph>(graph: &G, adj: &[Vec<bool>], node: Node) -> f64 {
+    let neighbors = graph.neighbors(node);
+    let pairs = neighbors.len() * neighbors.len().saturating_sub(1) / 2;
+    if pairs == 0 {
+        return 0.0;
+    }
+    let closed = neighbors
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &a)| neighbors[i + 1..].iter().map(move |&b| (a, b)))
+        .filter(|&(a, b)| adj[a][b])
+        .count();
+    closed as f64 / pairs as f64
+}
+
+/// Turns graphs into fixed-length feature vectors, to train classifiers on.
+///
+/// The features are, in order:
+///
+/// * the numbers of nodes and edges,
+/// * the minimum, maximum and mean degree and the standard deviation of the degrees,
+/// * the numbers of triangles and `K4`s,
+/// * the largest and smallest adjacency eigenvalues, and the Hoffman bound
+///   `1 - λ_max / λ_min` on the chromatic number,
+/// * the mean local clustering coefficient and the transitivity (the fraction of closed paths
+///   of length 2),
+/// * a histogram of the Weisfeiler-Lehman labels of the nodes, hashed into a fixed number of
+///   buckets and normalized by the number of nodes.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::graph::VecVecGraph;
+/// # use ml_cnp::learn::FeatureExtractor;
+///
+/// let mut cycle = VecVecGraph::new(5);
+/// for i in 0..5 {
+///     cycle.add_edge(i, (i + 1) % 5);
+/// }
+///
+/// let extractor = FeatureExtractor::new(8);
+/// let features = extractor.extract(&cycle);
+/// assert_eq!(features.len(), extractor.dimension());
+/// assert_eq!(features[2], 2.0); // min degree
+/// assert!((features[8] - 2.0).abs() < 1e-9); // largest eigenvalue of a cycle
+/// ```
+#[derive(Debug, Clone)]
+pub struct FeatureExtractor {
+    wl_buckets: usize,
+    hasher: WLHasher,
+}
+
+impl FeatureExtractor {
+    /// An extractor with a WL histogram of `wl_buckets` buckets.
+    pub fn new(wl_buckets: usize) -> Self {
+        Self {
+            wl_buckets,
+            hasher: WLHasher::default(),
+        }
+    }
+
+    /// Uses another hasher for the WL histogram, e.g. with more rounds.
+    pub fn with_hasher(mut self, hasher: WLHasher) -> Self {
+        self.hasher = hasher;
+        self
+    }
+
+    /// Length of the feature vectors.
+    pub fn dimension(&self) -> usize {
+        SCALAR_FEATURES.len() + self.wl_buckets
+    }
+
+    /// Names of the features, in order.
+    pub fn names(&self) -> Vec<String> {
+        SCALAR_FEATURES
+            .iter()
+            .map(|name| name.to_string())
+            .chain((0..self.wl_buckets).map(|i| format!("wl_{i}")))
+            .collect()
+    }
+
+    pub fn extract<G: Graph>(&self, graph: &G) -> DVector<f64> {
+        let size = graph.size();
+        let n = size.max(1) as f64;
+        let adj = adjacency(graph);
+        let degrees: Vec<f64> = (0..size)
+            .map(|node| graph.neighbors(node).len() as f64)
+            .collect();
+        let mean_degree = degrees.iter().sum::<f64>() / n;
+        let variance = degrees
+            .iter()
+            .map(|d| (d - mean_degree).powi(2))
+            .sum::<f64>()
+            / n;
+
+        let triangles = count_cliques(graph, 3) as f64;
+        let paths: f64 = degrees.iter().map(|d| d * (d - 1.0) / 2.0).sum();
+        let transitivity = if paths == 0.0 {
+            0.0
+        } else {
+            3.0 * triangles / paths
+        };
+        let mean_clustering = (0..size)
+            .map(|node| local_clustering(graph, &adj, node))
+            .sum::<f64>()
+            / n;
+
+        let spectrum = Spectrum::new(graph);
+
+        let mut features = vec![
+            size as f64,
+            graph.edges().len() as f64 / 2.0,
+            degrees.iter().cloned().reduce(f64::min).unwrap_or(0.0),
+            degrees.iter().cloned().reduce(f64::max).unwrap_or(0.0),
+            mean_degree,
+            variance.sqrt(),
+            triangles,
+            count_cliques(graph, 4) as f64,
+            spectrum.max_eigenvalue(),
+            spectrum.min_eigenvalue(),
+            spectrum.hoffman_bound(),
+            mean_clustering,
+            transitivity,
+        ];
+
+        let mut histogram = vec![0.0; self.wl_buckets];
+        if self.wl_buckets > 0 {
+            for label in self.hasher.labels(graph) {
+                histogram[(label % self.wl_buckets as u64) as usize] += 1.0 / n;
+            }
+        }
+        features.extend(histogram);
+
+        DVector::from_vec(features)
+    }
+
+    /// The features of many graphs, one row per graph.
+    pub fn extract_all<G: Graph + Sync>(&self, graphs: &[G]) -> DMatrix<f64> {
+        let rows: Vec<_> = graphs
+            .par_iter()
+            .map(|g| self.extract(g).transpose())
+            .collect();
+        if rows.is_empty() {
+            return DMatrix::zeros(0, self.dimension());
+        }
+        DMatrix::from_rows(&rows)
+    }
+}
+
+impl Default for FeatureExtractor {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
@@ -0,0 +1,364 @@
+use crate::graph::{Graph, VecVecGraph};
+use crate::learn::Example;
+use crate::learn::features::local_clustering;
+use crate::rng::SplitMix64;
+use crate::search::Scorer;
+use nalgebra::{DMatrix, DVector, RowDVector};
+use rayon::prelude::*;
+
+/// Number of input features of every node, see [`node_features`].
+const NODE_FEATURES: usize = 3;
+
+/// Initial features of the nodes: a constant, the degree and the local clustering coefficient.
+fn node_features<G: Graph>(graph: &G) -> DMatrix<f64> {
+    let adj = crate::graph::adjacency(graph);
+    DMatrix::from_fn(graph.size(), NODE_FEATURES, |node, k| match k {
+        0 => 1.0,
+        1 => graph.neighbors(node).len() as f64 / 10.0,
+        _ => local_clustering(graph, &adj, node),
+    })
+}
+
+/// Row `i` of the result is the mean of the rows of `h` at the neighbors of `i`.
+fn aggregate<G: Graph>(graph: &G, h: &DMatrix<f64>) -> DMatrix<f64> {
+    let mut res = DMatrix::zeros(h.nrows(), h.ncols());
+    for node in 0..graph.size() {
+        let neighbors = graph.neighbors(node);
+        for &neighbor in neighbors {
+            let mut row = res.row_mut(node);
+            row += h.row(neighbor) / neighbors.len() as f64;
+        }
+    }
+    res
+}
+
+/// The transpose of [`aggregate`], for backpropagation.
+fn aggregate_transpose<G: Graph>(graph: &G, h: &DMatrix<f64>) -> DMatrix<f64> {
+    let mut res = DMatrix::zeros(h.nrows(), h.ncols());
+    for node in 0..graph.size() {
+        let neighbors = graph.neighbors(node);
+        for &neighbor in neighbors {
+            let mut row = res.row_mut(neighbor);
+            row += h.row(node) / neighbors.len() as f64;
+        }
+    }
+    res
+}
+
+/// A message passing layer `relu(H W_self + mean_neighbors(H) W_neigh + b)`.
+#[derive(Debug, Clone, PartialEq)]
+struct Layer {
+    w_self: DMatrix<f64>,
+    w_neigh: DMatrix<f64>,
+    bias: RowDVector<f64>,
+}
+
+/// Values of a layer kept from the forward pass for backpropagation.
+struct Cache {
+    input: DMatrix<f64>,
+    aggregated: DMatrix<f64>,
+    pre_activation: DMatrix<f64>,
+}
+
+/// Options of [`Gnn::train`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrainConfig {
+    pub epochs: usize,
+    /// Step size of the Adam optimizer.
+    pub learning_rate: f64,
+}
+
+impl Default for TrainConfig {
+    fn default() -> Self {
+        Self {
+            epochs: 200,
+            learning_rate: 0.01,
+        }
+    }
+}
+
+/// A small graph neural network predicting the probability that a graph is not
+/// `k`-colorable, for the `k` of the examples it was trained on.
+///
+/// Nodes start from a few local features (degree, clustering coefficient), go through
+/// message passing layers averaging over neighbors, and the mean of the final node states is
+/// mapped to a probability by a logistic regression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gnn {
+    layers: Vec<Layer>,
+    readout: DVector<f64>,
+    readout_bias: f64,
+}
+
+impl Gnn {
+    /// A network with message passing layers of the given widths, randomly initialized.
+    pub fn new(hidden: &[usize], seed: u64) -> Self {
+        let mut rng = SplitMix64::new(seed);
+        // uniform Glorot initialization
+        let mut init = |rows: usize, cols: usize| {
+            let bound = (6.0 / (rows + cols) as f64).sqrt();
+            DMatrix::from_fn(rows, cols, |_, _| (2.0 * rng.next_f64() - 1.0) * bound)
+        };
+
+        let mut layers = vec![];
+        let mut width = NODE_FEATURES;
+        for &next in hidden {
+            layers.push(Layer {
+                w_self: init(width, next),
+                w_neigh: init(width, next),
+                bias: RowDVector::zeros(next),
+            });
+            width = next;
+        }
+        let readout = init(width, 1).column(0).into_owned();
+
+        Self {
+            layers,
+            readout,
+            readout_bias: 0.0,
+        }
+    }
+
+    /// Probability that the graph is not colorable.
+    pub fn predict<G: Graph>(&self, graph: &G) -> f64 {
+        sigmoid(self.forward(graph).0)
+    }
+
+    /// Mean binary cross-entropy over the examples.
+    pub fn loss(&self, examples: &[Example]) -> f64 {
+        let total: f64 = examples
+            .par_iter()
+            .map(|e| {
+                let logit = self.forward(&e.graph).0;
+                softplus(logit) - target(e) * logit
+            })
+            .sum();
+        total / examples.len().max(1) as f64
+    }
+
+    /// Trains the network with full-batch Adam on the binary cross-entropy.
+    ///
+    /// Uncolorable graphs are usually rare among the candidates of a layer, so both classes
+    /// are weighted to contribute equally to the loss.
+    ///
+    /// # Returns
+    ///
+    /// The weighted loss before every epoch.
+    pub fn train(&mut self, examples: &[Example], config: &TrainConfig) -> Vec<f64> {
+        let positives = examples.iter().filter(|e| e.uncolorable).count();
+        let negatives = examples.len() - positives;
+        let weight = |e: &Example| {
+            let class = if e.uncolorable { positives } else { negatives };
+            examples.len() as f64 / (2.0 * class as f64)
+        };
+
+        let mut adam = Adam::new(self, config.learning_rate);
+        let mut losses = vec![];
+        for _ in 0..config.epochs {
+            let (loss, mut grad) = examples
+                .par_iter()
+                .map(|e| self.gradient(&e.graph, target(e), weight(e)))
+                .reduce(
+                    || (0.0, self.zeros_like()),
+                    |(l1, mut g1), (l2, g2)| {
+                        g1.add_assign(&g2);
+                        (l1 + l2, g1)
+                    },
+                );
+
+            let n = examples.len().max(1) as f64;
+            losses.push(loss / n);
+            grad.scale(1.0 / n);
+            adam.step(self, &grad);
+        }
+        losses
+    }
+
+    /// The output logit, and the caches of every layer and the pooled state.
+    fn forward<G: Graph>(&self, graph: &G) -> (f64, Vec<Cache>, DVector<f64>) {
+        let mut h = node_features(graph);
+        let mut caches = vec![];
+        for layer in &self.layers {
+            let aggregated = aggregate(graph, &h);
+            let mut z = &h * &layer.w_self + &aggregated * &layer.w_neigh;
+            for mut row in z.row_iter_mut() {
+                row += &layer.bias;
+            }
+            let next = z.map(|x| x.max(0.0));
+            caches.push(Cache {
+                input: h,
+                aggregated,
+                pre_activation: z,
+            });
+            h = next;
+        }
+
+        let pooled = h.row_mean().transpose();
+        let logit = pooled.dot(&self.readout) + self.readout_bias;
+        (logit, caches, pooled)
+    }
+
+    /// The weighted loss of one example and its gradient with respect to every parameter.
+    fn gradient<G: Graph>(&self, graph: &G, target: f64, weight: f64) -> (f64, Gnn) {
+        let (logit, caches, pooled) = self.forward(graph);
+        let loss = weight * (softplus(logit) - target * logit);
+        let d_logit = weight * (sigmoid(logit) - target);
+
+        let mut grad = self.zeros_like();
+        grad.readout = &pooled * d_logit;
+        grad.readout_bias = d_logit;
+
+        let size = graph.size().max(1) as f64;
+        let d_pooled = (&self.readout * (d_logit / size)).transpose();
+        let mut d_h = DMatrix::from_fn(graph.size(), d_pooled.len(), |_, k| d_pooled[k]);
+
+        for (i, (layer, cache)) in self.layers.iter().zip(&caches).enumerate().rev() {
+            let d_z = d_h.zip_map(&cache.pre_activation, |d, z| if z > 0.0 { d } else { 0.0 });
+            grad.layers[i].w_self = cache.input.transpose() * &d_z;
+            grad.layers[i].w_neigh = cache.aggregated.transpose() * &d_z;
+            grad.layers[i].bias = d_z.row_sum();
+            d_h = &d_z * layer.w_self.transpose()
+                + aggregate_transpose(graph, &(&d_z * layer.w_neigh.transpose()));
+        }
+
+        (loss, grad)
+    }
+
+    fn zeros_like(&self) -> Self {
+        let mut res = self.clone();
+        for param in res.params_mut() {
+            param.fill(0.0);
+        }
+        res
+    }
+
+    fn add_assign(&mut self, other: &Self) {
+        for (a, b) in self.params_mut().into_iter().zip(other.params()) {
+            a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+        }
+    }
+
+    fn scale(&mut self, factor: f64) {
+        for param in self.params_mut() {
+            param.iter_mut().for_each(|x| *x *= factor);
+        }
+    }
+
+    fn params(&self) -> Vec<&[f64]> {
+        let mut res = vec![];
+        for layer in &self.layers {
+            res.push(layer.w_self.as_slice());
+            res.push(layer.w_neigh.as_slice());
+            res.push(layer.bias.as_slice());
+        }
+        res.push(self.readout.as_slice());
+        res.push(std::slice::from_ref(&self.readout_bias));
+        res
+    }
+
+    fn params_mut(&mut self) -> Vec<&mut [f64]> {
+        let mut res = vec![];
+        for layer in &mut self.layers {
+            res.push(layer.w_self.as_mut_slice());
+            res.push(layer.w_neigh.as_mut_slice());
+            res.push(layer.bias.as_mut_slice());
+        }
+        res.push(self.readout.as_mut_slice());
+        res.push(std::slice::from_mut(&mut self.readout_bias));
+        res
+    }
+}
+
+/// Ranks candidates by the predicted probability that they are not colorable.
+impl Scorer for Gnn {
+    fn name(&self) -> &str {
+        "gnn"
+    }
+
+    fn score(&self, graph: &VecVecGraph) -> f64 {
+        self.predict(graph)
+    }
+}
+
+/// The Adam optimizer, with the usual decay rates.
+struct Adam {
+    learning_rate: f64,
+    steps: i32,
+    m: Vec<Vec<f64>>,
+    v: Vec<Vec<f64>>,
+}
+
+impl Adam {
+    const BETA1: f64 = 0.9;
+    const BETA2: f64 = 0.999;
+    const EPSILON: f64 = 1e-8;
+
+    fn new(model: &Gnn, learning_rate: f64) -> Self {
+        let zeros: Vec<_> = model.params().iter().map(|p| vec![0.0; p.len()]).collect();
+        Self {
+            learning_rate,
+            steps: 0,
+            m: zeros.clone(),
+            v: zeros,
+        }
+    }
+
+    fn step(&mut self, model: &mut Gnn, grad: &Gnn) {
+        self.steps += 1;
+        let correction1 = 1.0 - Self::BETA1.powi(self.steps);
+        let correction2 = 1.0 - Self::BETA2.powi(self.steps);
+
+        let params = model.params_mut().into_iter().zip(grad.params());
+        for ((param, grad), (m, v)) in params.zip(self.m.iter_mut().zip(&mut self.v)) {
+            for k in 0..param.len() {
+                m[k] = Self::BETA1 * m[k] + (1.0 - Self::BETA1) * grad[k];
+                v[k] = Self::BETA2 * v[k] + (1.0 - Self::BETA2) * grad[k] * grad[k];
+                let m_hat = m[k] / correction1;
+                let v_hat = v[k] / correction2;
+                param[k] -= self.learning_rate * m_hat / (v_hat.sqrt() + Self::EPSILON);
+            }
+        }
+    }
+}
+
+fn target(example: &Example) -> f64 {
+    if example.uncolorable { 1.0 } else { 0.0 }
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// `ln(1 + e^x)`, computed without overflow.
+fn softplus(x: f64) -> f64 {
+    x.max(0.0) + (-x.abs()).exp().ln_1p()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_graph_from_str;
+
+    #[test]
+    fn test_gradient() {
+        let graph = build_graph_from_str("5\n0 1\n1 2\n2 3\n3 4\n4 0\n0 2").unwrap();
+        let mut model = Gnn::new(&[4, 3], 1);
+        let (_, grad) = model.gradient(graph.as_ref(), 1.0, 2.0);
+
+        // compare with central finite differences
+        let h = 1e-6;
+        let grads: Vec<Vec<f64>> = grad.params().iter().map(|p| p.to_vec()).collect();
+        for (i, param) in grads.iter().enumerate() {
+            for (k, &expected) in param.iter().enumerate() {
+                model.params_mut()[i][k] += h;
+                let plus = model.gradient(graph.as_ref(), 1.0, 2.0).0;
+                model.params_mut()[i][k] -= 2.0 * h;
+                let minus = model.gradient(graph.as_ref(), 1.0, 2.0).0;
+                model.params_mut()[i][k] += h;
+
+                let numeric = (plus - minus) / (2.0 * h);
+                assert!((numeric - expected).abs() < 1e-5, "{numeric} {expected}");
+            }
+        }
+    }
+}
@@ -1,36 +1,27 @@
 use crate::graph::VecVecGraph;
+use crate::io::IoError;
 use std::rc::Rc;
 
 pub mod algo;
+pub mod cli;
 pub mod constants;
+pub mod exact;
 pub mod graph;
+pub mod io;
+pub mod learn;
 pub mod linalg;
+pub mod optim;
+mod rng;
 pub mod search;
+pub mod spatial;
+pub mod verify;
 
 pub type Node = usize;
 pub type Color = usize;
 
-/// A simple parser for graph description in the following format:
-/// - First line: number of nodes
-/// - Subsequent lines: edges in the format "from to"
-pub fn build_graph_from_str(desc: &str) -> Rc<VecVecGraph> {
-    let mut lines = desc.lines();
-    let size: usize = lines
-        .next()
-        .expect("Graph description must start with number of nodes")
-        .trim()
-        .parse()
-        .expect("Invalid number of nodes");
-    let mut graph = VecVecGraph::new(size);
-    for line in lines {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() == 2 {
-            let from: usize = parts[0].parse().expect("Invalid node index");
-            let to: usize = parts[1].parse().expect("Invalid node index");
-            graph.add_edge(from, to);
-        } else {
-            panic!("Invalid edge description");
-        }
-    }
-    Rc::new(graph)
+/// Parses a graph description, see [`io::parse_graph`] for the supported formats.
+///
+/// The native format is the number of nodes on the first line, then an edge `from to` per line.
+pub fn build_graph_from_str(desc: &str) -> Result<Rc<VecVecGraph>, IoError> {
+    io::parse_graph(desc).map(Rc::new)
 }
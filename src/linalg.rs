@@ -1,4 +1,4 @@
-use nalgebra::{Matrix3, Point3};
+use nalgebra::{Matrix3, Point, Point3, SVector};
 
 /// Rotates a 3D point around a specified axis by a given angle.
 ///
@@ -49,37 +49,171 @@ pub(crate) fn rotate_point(
     axis_start + rotated_translated
 }
 
-/// Projects a given 3D point onto a line defined by two points.
+/// Orthonormal basis of the directions of the affine flat spanned by `flat`.
 ///
-/// # Arguments
-/// * `point` - A reference to the 3D point to be projected, of type `Point3<f64>`.
-/// * `axis_a` - A reference to one of the two 3D points that define the line, of type `Point3<f64>`.
-/// * `axis_b` - A reference to the other 3D point defining the line, of type `Point3<f64>`.
+/// Points that are affinely dependent on the previous ones do not add a direction, so the
+/// basis can have fewer than `flat.len() - 1` vectors.
+pub(crate) fn flat_basis<const D: usize>(flat: &[Point<f64, D>]) -> Vec<SVector<f64, D>> {
+    let mut basis: Vec<SVector<f64, D>> = vec![];
+    for p in flat.iter().skip(1) {
+        let mut v = p - flat[0];
+        for b in &basis {
+            v -= b * b.dot(&v);
+        }
+        if v.norm_squared() > 1e-10 {
+            basis.push(v.normalize());
+        }
+    }
+    basis
+}
+
+/// Projects a point onto the affine flat spanned by `flat`, e.g. onto the line through two
+/// points in 3D.
+pub(crate) fn point_to_flat_proj<const D: usize>(
+    point: &Point<f64, D>,
+    flat: &[Point<f64, D>],
+) -> Point<f64, D> {
+    let ap = point - flat[0];
+    flat_basis(flat)
+        .iter()
+        .fold(flat[0], |proj, b| proj + b * b.dot(&ap))
+}
+
+/// A rotation of `D`-space fixing a `(D - 2)`-dimensional affine flat pointwise.
 ///
-/// # Returns
-/// * A `Point3<f64>` representing the projection of the input point onto the line.
-pub(crate) fn point_to_line_proj(
-    point: &Point3<f64>,
-    axis_a: &Point3<f64>,
-    axis_b: &Point3<f64>,
-) -> Point3<f64> {
-    let ab = axis_b - axis_a;
+/// In the plane this is a rotation around a point, in space a rotation around an axis. The
+/// orthogonal complement of the flat is a plane, spanned by `u` and `v`, in which the rotation
+/// takes place. Its orientation is arbitrary but fixed for a given flat.
+pub(crate) struct FlatRotation<const D: usize> {
+    origin: Point<f64, D>,
+    u: SVector<f64, D>,
+    v: SVector<f64, D>,
+    cos_theta: f64,
+    sin_theta: f64,
+}
+
+impl<const D: usize> FlatRotation<D> {
+    /// Returns [`None`] if the points in `flat` do not span a `(D - 2)`-flat.
+    pub(crate) fn new(flat: &[Point<f64, D>], angle_rad: f64) -> Option<Self> {
+        let mut basis = flat_basis(flat);
+        if D < 2 || basis.len() != D - 2 {
+            return None;
+        }
+
+        // complete the basis with the standard basis vectors that are furthest from its span
+        let dims = basis.len();
+        for _ in 0..2 {
+            let residual = |k: usize| {
+                let mut e = SVector::<f64, D>::zeros();
+                e[k] = 1.0;
+                for b in &basis {
+                    e -= b * b[k];
+                }
+                e
+            };
+            let best = (0..D)
+                .map(residual)
+                .max_by(|a, b| a.norm_squared().total_cmp(&b.norm_squared()))
+                .unwrap();
+            basis.push(best.normalize());
+        }
+
+        Some(Self {
+            origin: flat[0],
+            u: basis[dims],
+            v: basis[dims + 1],
+            cos_theta: angle_rad.cos(),
+            sin_theta: angle_rad.sin(),
+        })
+    }
+
+    /// The rotation around `center` in the plane through `center`, `from` and `to`, that maps
+    /// `from` onto `to`.
+    ///
+    /// Returns [`None`] if `from` and `to` are not at the same distance from `center`, or if the
+    /// three points are collinear.
+    pub(crate) fn mapping(
+        center: &Point<f64, D>,
+        from: &Point<f64, D>,
+        to: &Point<f64, D>,
+    ) -> Option<Self> {
+        let (a, b) = (from - center, to - center);
+        if (a.norm() - b.norm()).abs() > 1e-8 || a.norm_squared() < 1e-10 {
+            return None;
+        }
 
-    let ap = point - axis_a;
+        let u = a.normalize();
+        let v = b - u * u.dot(&b);
+        if v.norm_squared() < 1e-10 {
+            return None;
+        }
 
-    if ab.norm_squared() < 1e-10 {
-        return *axis_a;
+        let angle = v.norm().atan2(u.dot(&b));
+        Some(Self {
+            origin: *center,
+            u,
+            v: v.normalize(),
+            cos_theta: angle.cos(),
+            sin_theta: angle.sin(),
+        })
     }
 
-    let t = ap.dot(&ab) / ab.norm_squared();
+    /// Angles of the rotations around `flat` that move `moving` to distance `dist` from `fixed`.
+    ///
+    /// `moving` runs on a circle in the plane of rotation, so there are zero, one or two such
+    /// angles, matching the orientation of [`FlatRotation::new`].
+    pub(crate) fn angles_to_distance(
+        flat: &[Point<f64, D>],
+        moving: &Point<f64, D>,
+        fixed: &Point<f64, D>,
+        dist: f64,
+    ) -> Vec<f64> {
+        let Some(rotation) = Self::new(flat, 0.0) else {
+            return vec![];
+        };
+        let (w, r) = (moving - rotation.origin, fixed - rotation.origin);
+        let (wu, wv, ru, rv) = (
+            w.dot(&rotation.u),
+            w.dot(&rotation.v),
+            r.dot(&rotation.u),
+            r.dot(&rotation.v),
+        );
+        // components along the flat do not change with the angle
+        let along =
+            (w - rotation.u * wu - rotation.v * wv) - (r - rotation.u * ru - rotation.v * rv);
+
+        let (rho, sigma) = (wu.hypot(wv), ru.hypot(rv));
+        if rho * sigma < 1e-10 {
+            return vec![];
+        }
+        // |moving(θ) - fixed|² = ρ² + σ² + |along|² - 2ρσ·cos(α + θ - β)
+        let cos =
+            (rho * rho + sigma * sigma + along.norm_squared() - dist * dist) / (2.0 * rho * sigma);
+        if cos.abs() > 1.0 {
+            return vec![];
+        }
+        let base = rv.atan2(ru) - wv.atan2(wu);
+        let delta = cos.acos();
+        if delta == 0.0 {
+            vec![base]
+        } else {
+            vec![base + delta, base - delta]
+        }
+    }
 
-    axis_a + ab * t
+    pub(crate) fn apply(&self, point: &Point<f64, D>) -> Point<f64, D> {
+        let w = point - self.origin;
+        let (a, b) = (w.dot(&self.u), w.dot(&self.v));
+        let a_rot = a * self.cos_theta - b * self.sin_theta;
+        let b_rot = a * self.sin_theta + b * self.cos_theta;
+        point + self.u * (a_rot - a) + self.v * (b_rot - b)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use approx::assert_abs_diff_eq;
+    use approx::{AbsDiffEq, assert_abs_diff_eq};
     use std::f64::consts::PI;
 
     #[test]
@@ -91,4 +225,36 @@ mod tests {
         let rotated = rotate_point(&original, &x, &y, PI / 2.0);
         assert_abs_diff_eq!(rotated, Point3::new(2.0, 0.0, 0.0));
     }
+
+    #[test]
+    fn test_flat_rotation() {
+        let original = Point3::new(0.0, 0.0, 0.0);
+        let x = Point3::new(1.0, 1.0, 0.0);
+        let y = Point3::new(1.0, 1.0, 1.0);
+
+        // same plane of rotation as `rotate_point`, possibly with the opposite orientation
+        let rotation = FlatRotation::new(&[x, y], PI / 2.0).unwrap();
+        let rotated = rotation.apply(&original);
+        let expected = [Point3::new(2.0, 0.0, 0.0), Point3::new(0.0, 2.0, 0.0)];
+        assert!(expected.iter().any(|e| e.abs_diff_eq(&rotated, 1e-10)));
+
+        // a 4D rotation fixes its 2-flat and preserves distances to it
+        let flat = [
+            Point::from([0.0, 0.0, 0.0, 0.0]),
+            Point::from([1.0, 0.0, 0.0, 0.0]),
+            Point::from([0.0, 1.0, 1.0, 0.0]),
+        ];
+        let rotation = FlatRotation::new(&flat, 1.0).unwrap();
+        for p in &flat {
+            assert_abs_diff_eq!(rotation.apply(p), *p, epsilon = 1e-10);
+        }
+        let p = Point::from([0.3, 0.5, -0.2, 0.7]);
+        let proj = point_to_flat_proj(&p, &flat);
+        assert_abs_diff_eq!(
+            (rotation.apply(&p) - proj).norm(),
+            (p - proj).norm(),
+            epsilon = 1e-10
+        );
+        assert!((rotation.apply(&p) - p).norm() > 0.1);
+    }
 }
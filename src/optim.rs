@@ -0,0 +1,5 @@
+mod sdp;
+mod simplex;
+
+pub use sdp::{Sdp, SdpConstraint, SdpSolution};
+pub use simplex::{LinearProgram, LpError, LpSolution};
@@ -0,0 +1,243 @@
+use crate::rng::SplitMix64;
+use nalgebra::DMatrix;
+use std::collections::VecDeque;
+
+/// A linear constraint `⟨A, X⟩ = b` on the matrix variable of a [`Sdp`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdpConstraint {
+    /// The entries `(i, j, a)` of the symmetric matrix `A`, with `i ≤ j`. An off-diagonal entry
+    /// stands for both `A_ij` and `A_ji`.
+    pub entries: Vec<(usize, usize, f64)>,
+    pub rhs: f64,
+}
+
+impl SdpConstraint {
+    /// `⟨A, V Vᵀ⟩`.
+    fn apply(&self, v: &DMatrix<f64>) -> f64 {
+        self.entries
+            .iter()
+            .map(|&(i, j, a)| {
+                let dot = v.row(i).dot(&v.row(j));
+                if i == j { a * dot } else { 2.0 * a * dot }
+            })
+            .sum()
+    }
+
+    /// Adds `factor` times the gradient of `⟨A, V Vᵀ⟩`, i.e. `2 A V`, to `grad`.
+    fn add_gradient(&self, v: &DMatrix<f64>, factor: f64, grad: &mut DMatrix<f64>) {
+        for &(i, j, a) in &self.entries {
+            let mut row = grad.row_mut(i);
+            row += v.row(j) * (2.0 * a * factor);
+            if i != j {
+                let mut row = grad.row_mut(j);
+                row += v.row(i) * (2.0 * a * factor);
+            }
+        }
+    }
+}
+
+/// An approximate solution of a [`Sdp`], as the factor `V` of `X = V Vᵀ`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdpSolution {
+    /// `⟨C, X⟩`.
+    pub value: f64,
+    pub factor: DMatrix<f64>,
+    /// Lagrange multipliers of the constraints.
+    pub multipliers: Vec<f64>,
+    /// The largest violation `|⟨A, X⟩ - b|` of a constraint.
+    pub violation: f64,
+}
+
+/// The semidefinite program: maximize `⟨C, X⟩` subject to `⟨A_k, X⟩ = b_k` and `X ⪰ 0`.
+///
+/// It is solved with the low-rank method of Burer and Monteiro: `X` is written `V Vᵀ` with `V`
+/// of a few columns, which keeps it positive semidefinite, and the constraints are enforced by
+/// an augmented Lagrangian, minimized by L-BFGS. The problem in `V` is not convex,
+/// but for a rank above `√(2m)`, with `m` constraints, its local optima are usually global.
+///
+/// The solution is only approximately feasible, see [`SdpSolution::violation`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sdp {
+    pub objective: DMatrix<f64>,
+    pub constraints: Vec<SdpConstraint>,
+    rank: usize,
+    tolerance: f64,
+    seed: u64,
+}
+
+impl Sdp {
+    /// The maximal number of augmented Lagrangian updates.
+    const OUTER_ITERATIONS: usize = 100;
+    /// The maximal number of L-BFGS steps per update.
+    const INNER_ITERATIONS: usize = 500;
+
+    pub fn new(objective: DMatrix<f64>, constraints: Vec<SdpConstraint>) -> Self {
+        assert!(
+            objective.is_square(),
+            "the objective must be a square matrix"
+        );
+        let rank = ((2.0 * constraints.len() as f64).sqrt().ceil() as usize + 1)
+            .min(objective.nrows())
+            .max(1);
+        Self {
+            objective,
+            constraints,
+            rank,
+            tolerance: 1e-5,
+            seed: 0,
+        }
+    }
+
+    /// Number of columns of the factor `V`.
+    pub fn with_rank(mut self, rank: usize) -> Self {
+        self.rank = rank;
+        self
+    }
+
+    /// The largest constraint violation accepted.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Seed of the random starting point.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn solve(&self) -> SdpSolution {
+        let size = self.objective.nrows();
+        let mut rng = SplitMix64::new(self.seed);
+        let mut v = DMatrix::from_fn(size, self.rank, |_, _| rng.next_f64() - 0.5);
+        let mut multipliers = vec![0.0; self.constraints.len()];
+        let mut penalty = 1.0;
+        let mut violation = self.violation(&v);
+
+        for _ in 0..Self::OUTER_ITERATIONS {
+            // the subproblems only need to be solved as accurately as the constraints hold
+            let accuracy = self.tolerance.max(0.1 * violation.min(1.0));
+            self.minimize(&mut v, &multipliers, penalty, accuracy);
+
+            let residuals = self.residuals(&v);
+            for (y, r) in multipliers.iter_mut().zip(&residuals) {
+                *y += penalty * r;
+            }
+            let next = residuals.iter().fold(0.0, |acc: f64, r| acc.max(r.abs()));
+            if next < self.tolerance {
+                violation = next;
+                break;
+            }
+            if next > 0.25 * violation {
+                penalty *= 10.0;
+            }
+            violation = next;
+        }
+
+        SdpSolution {
+            value: (&v.transpose() * &self.objective * &v).trace(),
+            factor: v,
+            multipliers,
+            violation,
+        }
+    }
+
+    fn residuals(&self, v: &DMatrix<f64>) -> Vec<f64> {
+        self.constraints
+            .iter()
+            .map(|c| c.apply(v) - c.rhs)
+            .collect()
+    }
+
+    fn violation(&self, v: &DMatrix<f64>) -> f64 {
+        self.residuals(v)
+            .iter()
+            .fold(0.0, |acc: f64, r| acc.max(r.abs()))
+    }
+
+    /// The augmented Lagrangian `-⟨C, X⟩ + Σ y_k r_k + σ/2 Σ r_k²`, where `r_k` are the
+    /// residuals of the constraints, and its gradient.
+    fn lagrangian(
+        &self,
+        v: &DMatrix<f64>,
+        multipliers: &[f64],
+        penalty: f64,
+    ) -> (f64, DMatrix<f64>) {
+        let cv = &self.objective * v;
+        let mut value = -(v.transpose() * &cv).trace();
+        let mut grad = cv * -2.0;
+        for (c, &y) in self.constraints.iter().zip(multipliers) {
+            let r = c.apply(v) - c.rhs;
+            value += y * r + penalty / 2.0 * r * r;
+            c.add_gradient(v, y + penalty * r, &mut grad);
+        }
+        (value, grad)
+    }
+
+    /// L-BFGS with a backtracking line search.
+    fn minimize(&self, v: &mut DMatrix<f64>, multipliers: &[f64], penalty: f64, accuracy: f64) {
+        const MEMORY: usize = 10;
+        let (mut value, mut grad) = self.lagrangian(v, multipliers, penalty);
+        // pairs of position and gradient differences of the last steps
+        let mut history: VecDeque<(DMatrix<f64>, DMatrix<f64>)> = VecDeque::new();
+
+        for _ in 0..Self::INNER_ITERATIONS {
+            if grad.norm() < accuracy {
+                return;
+            }
+
+            // two-loop recursion for the quasi-Newton direction
+            let mut q = grad.clone();
+            let mut alphas = vec![];
+            for (s, y) in history.iter().rev() {
+                let alpha = s.dot(&q) / y.dot(s);
+                q -= y * alpha;
+                alphas.push(alpha);
+            }
+            if let Some((s, y)) = history.back() {
+                q *= s.dot(y) / y.norm_squared();
+            }
+            for ((s, y), alpha) in history.iter().zip(alphas.into_iter().rev()) {
+                let beta = y.dot(&q) / y.dot(s);
+                q += s * (alpha - beta);
+            }
+            let mut direction = -q;
+            let mut slope = grad.dot(&direction);
+            if slope >= 0.0 {
+                // not a descent direction, fall back to the gradient
+                history.clear();
+                direction = -&grad;
+                slope = -grad.norm_squared();
+            }
+
+            let mut step = if history.is_empty() {
+                1.0 / grad.norm().max(1.0)
+            } else {
+                1.0
+            };
+            let (next, next_value, next_grad) = loop {
+                let next = &*v + &direction * step;
+                let (next_value, next_grad) = self.lagrangian(&next, multipliers, penalty);
+                if next_value <= value + 1e-4 * step * slope {
+                    break (next, next_value, next_grad);
+                }
+                step /= 2.0;
+                if step < 1e-20 {
+                    return;
+                }
+            };
+
+            let s = &next - &*v;
+            let y = &next_grad - &grad;
+            if s.dot(&y) > 1e-12 {
+                if history.len() == MEMORY {
+                    history.pop_front();
+                }
+                history.push_back((s, y));
+            }
+            *v = next;
+            value = next_value;
+            grad = next_grad;
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use nalgebra::{DMatrix, DVector};
+use std::fmt;
+
+/// Reduced costs and ratios smaller than this are treated as zero.
+const TOLERANCE: f64 = 1e-9;
+
+/// An error met while solving a [`LinearProgram`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LpError {
+    /// The objective can grow without bound.
+    Unbounded,
+    /// Some bound is negative, so the origin is not feasible. Such programs need a first phase
+    /// to find a feasible point, which is not implemented.
+    NegativeBound,
+}
+
+impl fmt::Display for LpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LpError::Unbounded => write!(f, "the linear program is unbounded"),
+            LpError::NegativeBound => write!(f, "the linear program has a negative bound"),
+        }
+    }
+}
+
+impl std::error::Error for LpError {}
+
+/// An optimal solution of a [`LinearProgram`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LpSolution {
+    pub value: f64,
+    pub x: DVector<f64>,
+    /// An optimal solution of the dual program: the shadow price of every constraint.
+    pub duals: DVector<f64>,
+}
+
+/// The linear program: maximize `c·x` subject to `A x ≤ b` and `x ≥ 0`, where `b ≥ 0`.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::optim::LinearProgram;
+/// # use nalgebra::{dmatrix, dvector};
+///
+/// // maximize x + y subject to x + 2y ≤ 4 and 3x + y ≤ 6
+/// let lp = LinearProgram::new(
+///     dvector![1.0, 1.0],
+///     dmatrix![1.0, 2.0; 3.0, 1.0],
+///     dvector![4.0, 6.0],
+/// );
+/// let solution = lp.solve().unwrap();
+/// assert!((solution.value - 2.8).abs() < 1e-9);
+/// assert!((solution.duals[0] - 0.4).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearProgram {
+    pub objective: DVector<f64>,
+    pub constraints: DMatrix<f64>,
+    pub bounds: DVector<f64>,
+}
+
+impl LinearProgram {
+    pub fn new(objective: DVector<f64>, constraints: DMatrix<f64>, bounds: DVector<f64>) -> Self {
+        assert_eq!(
+            constraints.ncols(),
+            objective.len(),
+            "one column per variable"
+        );
+        assert_eq!(
+            constraints.nrows(),
+            bounds.len(),
+            "one bound per constraint"
+        );
+        Self {
+            objective,
+            constraints,
+            bounds,
+        }
+    }
+
+    /// Solves the program with the simplex method on a dense tableau, starting from the origin
+    /// and using Bland's rule, which can not cycle.
+    pub fn solve(&self) -> Result<LpSolution, LpError> {
+        if self.bounds.iter().any(|&b| b < 0.0) {
+            return Err(LpError::NegativeBound);
+        }
+
+        let (m, n) = self.constraints.shape();
+        // rows 0..m are the constraints with a slack variable each, row m the objective
+        let mut tableau = DMatrix::zeros(m + 1, n + m + 1);
+        tableau
+            .view_mut((0, 0), (m, n))
+            .copy_from(&self.constraints);
+        for i in 0..m {
+            tableau[(i, n + i)] = 1.0;
+            tableau[(i, n + m)] = self.bounds[i];
+        }
+        for j in 0..n {
+            tableau[(m, j)] = -self.objective[j];
+        }
+        let mut basis: Vec<usize> = (n..n + m).collect();
+
+        while let Some(entering) = (0..n + m).find(|&j| tableau[(m, j)] < -TOLERANCE) {
+            let leaving = (0..m)
+                .filter(|&i| tableau[(i, entering)] > TOLERANCE)
+                .min_by(|&a, &b| {
+                    let ratio_a = tableau[(a, n + m)] / tableau[(a, entering)];
+                    let ratio_b = tableau[(b, n + m)] / tableau[(b, entering)];
+                    ratio_a.total_cmp(&ratio_b).then(basis[a].cmp(&basis[b]))
+                })
+                .ok_or(LpError::Unbounded)?;
+            pivot(&mut tableau, leaving, entering);
+            basis[leaving] = entering;
+        }
+
+        let mut x = DVector::zeros(n);
+        for (i, &var) in basis.iter().enumerate() {
+            if var < n {
+                x[var] = tableau[(i, n + m)];
+            }
+        }
+        let duals = DVector::from_fn(m, |i, _| tableau[(m, n + i)]);
+
+        Ok(LpSolution {
+            value: tableau[(m, n + m)],
+            x,
+            duals,
+        })
+    }
+}
+
+fn pivot(tableau: &mut DMatrix<f64>, row: usize, col: usize) {
+    let pivot_row = tableau.row(row) / tableau[(row, col)];
+    tableau.set_row(row, &pivot_row);
+    for i in 0..tableau.nrows() {
+        if i != row {
+            let factor = tableau[(i, col)];
+            if factor != 0.0 {
+                let mut r = tableau.row_mut(i);
+                r -= &pivot_row * factor;
+            }
+        }
+    }
+}
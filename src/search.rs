@@ -1,14 +1,32 @@
+mod beam;
+mod config;
+mod mcts;
 mod misc;
+mod moves;
+mod provenance;
+mod score;
 
 use crate::constants::{DIST, EPS};
+use crate::graph::VecVecGraph;
 use crate::graph::hash::Hashable;
-use crate::graph::{Graph, VecVecGraph};
-use crate::linalg::rotate_point;
+use crate::graph::iso::is_isomorphic;
+use crate::io::LayerRecord;
+use crate::linalg::{FlatRotation, rotate_point};
+use crate::spatial::SpatialHash;
 use approx::AbsDiffEq;
-use nalgebra::Point3;
+pub use beam::{BeamResult, BeamSearch, Dedup, LayerReport, Scored};
+pub use config::{ConfigError, SearchConfig, config_dimension, moser_spindle};
+pub use mcts::{Mcts, MctsResult};
+pub use moves::{AxisRotation, MinkowskiSum, MoveGenerator, PivotRotation, Reflection, UnionWith};
+use nalgebra::{Point, Point3};
+pub use provenance::{AxisMove, Construction, Lineage, Provenance, Traced, next_layer_traced};
 use rayon::prelude::*;
+pub use score::{
+    CliqueCount, EdgeDensity, FractionalChromatic, GreedyColors, MinDegree, Scorer, Selection,
+    SpectralBound, ThetaBound,
+};
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 /// Rotates a set of 3D points around a specified axis defined by two points, `axis_a` and `axis_b`.
 ///
@@ -35,7 +53,7 @@ pub fn rotate(
     axis_b: &Point3<f64>,
     dist: f64,
 ) -> (Vec<Point3<f64>>, Vec<Point3<f64>>) {
-    let angle = misc::get_rotate_angle(target, axis_a, axis_b, dist);
+    let angle = misc::get_rotate_angle(target, &[*axis_a, *axis_b], dist);
 
     let res1 = points
         .iter()
@@ -50,15 +68,47 @@ pub fn rotate(
     (res1, res2)
 }
 
-/// Computes the number of critical pairs between two sets of 3D points.
+/// The two point sets obtained by rotating in both directions.
+type RotatedPair<const D: usize> = (Vec<Point<f64, D>>, Vec<Point<f64, D>>);
+
+/// Rotates a set of points in `D`-space around the `(D - 2)`-flat spanned by `flat`, by the
+/// angle that moves `target` to distance `dist` from its original position.
+///
+/// This generalizes [`rotate`] to any dimension: `flat` is a single point in the plane, two
+/// points of an axis in space, and `D - 1` points in general.
+///
+/// # Returns
+///
+/// The points rotated by the angle and by its negative, or [`None`] if `flat` does not span
+/// a `(D - 2)`-flat or `target` is too close to the flat to move by `dist`.
+pub fn rotate_around_flat<const D: usize>(
+    points: &[Point<f64, D>],
+    target: &Point<f64, D>,
+    flat: &[Point<f64, D>],
+    dist: f64,
+) -> Option<RotatedPair<D>> {
+    let angle = misc::get_rotate_angle(target, flat, dist);
+    if !angle.is_finite() {
+        return None;
+    }
+
+    let rotation1 = FlatRotation::new(flat, angle)?;
+    let rotation2 = FlatRotation::new(flat, -angle)?;
+    Some((
+        points.iter().map(|p| rotation1.apply(p)).collect(),
+        points.iter().map(|p| rotation2.apply(p)).collect(),
+    ))
+}
+
+/// Computes the number of critical pairs between two sets of points.
 ///
 /// A pair of points, one from each set, is considered a critical pair if the Euclidean
 /// distance between them is equal to the specified `dist` value, within a tolerance defined by `EPS`.
 ///
 /// # Arguments
 ///
-/// * `points1` - A slice of the first set of 3D points.
-/// * `points2` - A slice of the second set of 3D points.
+/// * `points1` - A slice of the first set of points.
+/// * `points2` - A slice of the second set of points.
 /// * `dist` - The target distance for which to find critical pairs.
 ///
 /// # Returns
@@ -68,7 +118,7 @@ pub fn rotate(
 /// # Examples
 ///
 /// ```
-/// # use nalgebra::Point3;
+/// # use nalgebra::{Point, Point3};
 /// # use ml_cnp::search::critical_pair_cnt;
 ///
 /// let points1 = vec![Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0)];
@@ -77,33 +127,32 @@ pub fn rotate(
 /// let cnt = critical_pair_cnt(&points1, &points2, dist);
 /// assert_eq!(cnt, 2);
 /// ```
-pub fn critical_pair_cnt(points1: &[Point3<f64>], points2: &[Point3<f64>], dist: f64) -> usize {
-    let mut cnt = 0;
-    for p1 in points1 {
-        for p2 in points2 {
-            if (p1 - p2).norm().abs_diff_eq(&dist, EPS) {
-                cnt += 1;
-            }
-        }
-    }
-
-    cnt
+pub fn critical_pair_cnt<const D: usize>(
+    points1: &[Point<f64, D>],
+    points2: &[Point<f64, D>],
+    dist: f64,
+) -> usize {
+    let index = SpatialHash::new(points2, dist);
+    points1
+        .iter()
+        .map(|p1| index.at_distance(p1, dist).count())
+        .sum()
 }
 
-/// Merges two slices of 3D points, `points1` and `points2`.
+/// Merges two slices of points, `points1` and `points2`.
 ///
 /// # Arguments
 ///
-/// * `points1` - A slice of 3D points.
-/// * `points2` - Another slice of 3D points.
+/// * `points1` - A slice of points.
+/// * `points2` - Another slice of points.
 ///
 /// # Returns
 ///
-/// A `Vec<Point3<f64>>` representing the merged list of points.
+/// A `Vec<Point<f64, D>>` representing the merged list of points.
 ///
 /// # Examples
 /// ```
-/// # use nalgebra::Point3;
+/// # use nalgebra::{Point, Point3};
 /// # use ml_cnp::search::merge;
 ///
 /// let points1 = vec![Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0)];
@@ -113,7 +162,10 @@ pub fn critical_pair_cnt(points1: &[Point3<f64>], points2: &[Point3<f64>], dist:
 ///
 /// assert_eq!(merged, vec![Point3::new(1.0, 0.0, 0.0), Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 0.0, 0.0)]);
 /// ```
-pub fn merge(points1: &[Point3<f64>], points2: &[Point3<f64>]) -> Vec<Point3<f64>> {
+pub fn merge<const D: usize>(
+    points1: &[Point<f64, D>],
+    points2: &[Point<f64, D>],
+) -> Vec<Point<f64, D>> {
     // first, find points in both points1 and points2
     let mut res: Vec<_> = points1
         .iter()
@@ -137,58 +189,254 @@ pub fn merge(points1: &[Point3<f64>], points2: &[Point3<f64>]) -> Vec<Point3<f64
     res
 }
 
-fn points_hash(points: &[Point3<f64>]) -> u64 {
-    let graph = VecVecGraph::from_points(points, DIST);
-    graph.hash()
+/// Calls `f` with every candidate obtained from `graph` by a single move, and the description
+/// of the move, see [`MoveGenerator::generate_traced`].
+fn for_each_candidate<const D: usize>(
+    graph: &[Point<f64, D>],
+    dist: f64,
+    moves: &[&dyn MoveGenerator<D>],
+    mut f: impl FnMut(Vec<Point<f64, D>>, &str),
+) {
+    for generator in moves {
+        generator.generate_traced(graph, dist, &mut f);
+    }
 }
 
 /// Generates the next layer of graphs by rotating and merging points.
 ///
-/// This function takes a slice of 3D point graphs and generates a new set of
+/// This function takes a slice of point graphs and generates a new set of
 /// graphs by performing rotations and merges on the input graphs. For each graph,
-/// it iterates over all pairs of points, rotates the graph around these points,
-/// and then merges the rotated graph back with the original. The resulting unique
+/// it iterates over all `D - 1` tuples of points (pairs in 3D, single points in the plane),
+/// rotates the graph around the flat they span, and then merges the rotated graph back
+/// with the original. The resulting unique
 /// graphs (based on a hash of their points) are collected and returned as a vector.
 ///
 /// # Arguments
 ///
-/// * `graphs` - A slice of 3D point graphs, where each graph is represented as a vector of `Point3<f64>`.
+/// * `graphs` - A slice of point graphs, where each graph is represented as a vector of `Point<f64, D>`.
 ///
 /// # Returns
 ///
-/// A `Vec<Vec<Point3<f64>>>` containing the newly generated graphs after rotation and merging.
-pub fn next_layer(graphs: &[Vec<Point3<f64>>]) -> Vec<Vec<Point3<f64>>> {
-    let graph_set = Mutex::new(HashMap::new());
-
-    graphs.par_iter().for_each(|graph| {
-        let len = graph.len();
-        for i in 0..len {
-            for j in i + 1..len {
-                for p in graph {
-                    if p.abs_diff_eq(&graph[i], EPS) || p.abs_diff_eq(&graph[j], EPS) {
-                        continue;
-                    }
+/// A `Vec<Vec<Point<f64, D>>>` containing the newly generated graphs after rotation and merging.
+pub fn next_layer<const D: usize>(graphs: &[Vec<Point<f64, D>>]) -> Vec<Vec<Point<f64, D>>> {
+    next_layer_at(graphs, DIST, &[&AxisRotation])
+}
+
+/// [`next_layer`] for an arbitrary target distance.
+pub(crate) fn next_layer_at<const D: usize>(
+    graphs: &[Vec<Point<f64, D>>],
+    dist: f64,
+    moves: &[&dyn MoveGenerator<D>],
+) -> Vec<Vec<Point<f64, D>>> {
+    expand_layer(graphs, dist, moves, false)
+        .0
+        .into_iter()
+        .map(|record| record.points)
+        .collect()
+}
+
+/// Statistics about hash collisions met by [`next_layer_checked`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CollisionStats {
+    /// Candidates whose hash matched a stored graph that is *not* isomorphic to them.
+    /// These are the configurations [`next_layer`] wrongly discards.
+    pub true_collisions: usize,
+    /// Candidates whose hash matched an isomorphic stored graph, i.e. genuine duplicates.
+    pub false_collisions: usize,
+}
 
-                    let (rotated1, rotated2) = rotate(graph, p, &graph[i], &graph[j], DIST);
-                    let merged = merge(&rotated1, graph);
-                    let hash = points_hash(&merged);
-                    graph_set.lock().unwrap().entry(hash).or_insert(merged);
+/// Same as [`next_layer`], but verifies every hash collision with an exact isomorphism test.
+///
+/// When a candidate has the same hash as an already stored graph, it is compared against every
+/// graph stored under that hash with [`is_isomorphic`]. It is discarded only if one of them is
+/// isomorphic to it, and kept alongside them otherwise.
+///
+/// # Returns
+///
+/// The unique graphs of the next layer, together with the [`CollisionStats`] met on the way.
+pub fn next_layer_checked<const D: usize>(
+    graphs: &[Vec<Point<f64, D>>],
+) -> (Vec<Vec<Point<f64, D>>>, CollisionStats) {
+    next_layer_checked_at(graphs, DIST, &[&AxisRotation])
+}
+
+/// [`next_layer_checked`] for an arbitrary target distance.
+pub(crate) fn next_layer_checked_at<const D: usize>(
+    graphs: &[Vec<Point<f64, D>>],
+    dist: f64,
+    moves: &[&dyn MoveGenerator<D>],
+) -> (Vec<Vec<Point<f64, D>>>, CollisionStats) {
+    let (records, stats) = expand_layer(graphs, dist, moves, true);
+    let layer = records.into_iter().map(|record| record.points).collect();
+    (layer, stats)
+}
 
-                    let merged = merge(&rotated2, graph);
-                    let hash = points_hash(&merged);
-                    graph_set.lock().unwrap().entry(hash).or_insert(merged);
+/// A distinct candidate of [`expand_layer`]. `order` is its parent and its rank among the
+/// candidates of the parent, the smallest being kept among duplicates.
+struct Expanded<const D: usize> {
+    graph: Option<Arc<VecVecGraph>>,
+    order: (usize, usize),
+    record: LayerRecord<D>,
+}
+
+impl<const D: usize> Expanded<D> {
+    fn keep_first(&mut self, order: (usize, usize), record: LayerRecord<D>) {
+        if order < self.order {
+            self.order = order;
+            self.record = record;
+        }
+    }
+}
+
+/// The distinct candidates generated from `graphs`, as records whose parent is an index in
+/// `graphs` and whose generator describes the move, e.g. an [`AxisMove`]. With `checked`, hash collisions are
+/// verified with [`is_isomorphic`].
+///
+/// Among candidates with the same graph, the first one generated from the first parent is
+/// kept, and the records are listed in that order, so the result does not depend on the order
+/// of the parallel search.
+pub(crate) fn expand_layer<const D: usize>(
+    graphs: &[Vec<Point<f64, D>>],
+    dist: f64,
+    moves: &[&dyn MoveGenerator<D>],
+    checked: bool,
+) -> (Vec<LayerRecord<D>>, CollisionStats) {
+    let graph_set: Mutex<HashMap<u64, Vec<Expanded<D>>>> = Mutex::new(HashMap::new());
+    let stats = Mutex::new(CollisionStats::default());
+
+    graphs.par_iter().enumerate().for_each(|(parent, graph)| {
+        let mut rank = 0;
+        for_each_candidate(graph, dist, moves, |points, name| {
+            let order = (parent, rank);
+            rank += 1;
+            let candidate = VecVecGraph::from_points(&points, dist);
+            let hash = candidate.hash();
+            let record = LayerRecord {
+                id: 0,
+                parent: Some(parent),
+                generator: Some(name.to_string()),
+                hash,
+                points,
+            };
+
+            if !checked {
+                let mut graph_set = graph_set.lock().unwrap();
+                let bucket = graph_set.entry(hash).or_default();
+                match bucket.first_mut() {
+                    Some(stored) => stored.keep_first(order, record),
+                    None => bucket.push(Expanded {
+                        graph: None,
+                        order,
+                        record,
+                    }),
                 }
+                return;
             }
-        }
+
+            // the isomorphism tests run without the lock, so graphs stored meanwhile are
+            // checked on the next round
+            let candidate = Arc::new(candidate);
+            let mut tested = 0;
+            loop {
+                let stored: Vec<_> = {
+                    let mut graph_set = graph_set.lock().unwrap();
+                    let bucket = graph_set.entry(hash).or_default();
+                    if bucket.len() == tested {
+                        if tested > 0 {
+                            stats.lock().unwrap().true_collisions += 1;
+                        }
+                        bucket.push(Expanded {
+                            graph: Some(candidate),
+                            order,
+                            record,
+                        });
+                        return;
+                    }
+                    bucket[tested..]
+                        .iter()
+                        .map(|stored| stored.graph.clone().unwrap())
+                        .collect()
+                };
+                if let Some(i) = stored
+                    .iter()
+                    .position(|graph| is_isomorphic(graph.as_ref(), candidate.as_ref()))
+                {
+                    stats.lock().unwrap().false_collisions += 1;
+                    graph_set.lock().unwrap().get_mut(&hash).unwrap()[tested + i]
+                        .keep_first(order, record);
+                    return;
+                }
+                tested += stored.len();
+            }
+        });
     });
 
-    graph_set.lock().unwrap().values().cloned().collect()
+    let mut expanded: Vec<_> = graph_set
+        .into_inner()
+        .unwrap()
+        .into_values()
+        .flatten()
+        .collect();
+    expanded.sort_by_key(|expanded| expanded.order);
+    let records = expanded
+        .into_iter()
+        .enumerate()
+        .map(|(id, expanded)| LayerRecord {
+            id,
+            ..expanded.record
+        })
+        .collect();
+    (records, stats.into_inner().unwrap())
+}
+
+/// Keeps the `target_size` candidates with the most edges per node.
+///
+/// See [`prune_layer_by`] for other scores and selection strategies.
+pub fn prune_layer<const D: usize>(
+    graphs: Vec<Vec<Point<f64, D>>>,
+    target_size: usize,
+) -> Vec<Vec<Point<f64, D>>> {
+    prune_layer_by(graphs, target_size, &EdgeDensity, Selection::TopK)
 }
 
-pub fn prune_layer(mut graphs: Vec<Vec<Point3<f64>>>, target_size: usize) -> Vec<Vec<Point3<f64>>> {
-    graphs.sort_by_key(|g| {
-        let graph = VecVecGraph::from_points(g, DIST);
-        graph.edges().len() / g.len()
-    });
-    graphs.into_iter().take(target_size).collect()
+/// Keeps `target_size` candidates, ranked by `scorer` and picked according to `selection`.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::search::{next_layer, prune_layer_by, GreedyColors, Selection};
+/// # use ml_cnp::constants::INIT_POINTS;
+///
+/// let layer = next_layer(&next_layer(&[INIT_POINTS.to_vec()]));
+/// let pruned = prune_layer_by(layer, 2, &GreedyColors, Selection::Diverse { max_similarity: 0.9 });
+/// assert_eq!(pruned.len(), 2);
+/// ```
+pub fn prune_layer_by<const D: usize>(
+    graphs: Vec<Vec<Point<f64, D>>>,
+    target_size: usize,
+    scorer: &dyn Scorer,
+    selection: Selection,
+) -> Vec<Vec<Point<f64, D>>> {
+    prune_layer_at(graphs, target_size, DIST, scorer, selection)
+}
+
+/// [`prune_layer_by`] for an arbitrary target distance.
+pub(crate) fn prune_layer_at<const D: usize>(
+    graphs: Vec<Vec<Point<f64, D>>>,
+    target_size: usize,
+    dist: f64,
+    scorer: &dyn Scorer,
+    selection: Selection,
+) -> Vec<Vec<Point<f64, D>>> {
+    let built: Vec<_> = graphs
+        .par_iter()
+        .map(|g| VecVecGraph::from_points(g, dist))
+        .collect();
+    let scores: Vec<_> = built.par_iter().map(|g| scorer.score(g)).collect();
+
+    let mut graphs: Vec<_> = graphs.into_iter().map(Some).collect();
+    score::select(&built, &scores, target_size, selection)
+        .into_iter()
+        .map(|i| graphs[i].take().unwrap())
+        .collect()
 }
@@ -0,0 +1,381 @@
+use crate::constants::DIST;
+use crate::graph::VecVecGraph;
+use crate::graph::iso::is_isomorphic;
+use crate::io::{
+    Checkpoint, IoError, LayerRecord, SearchSettings, layer_records, load_checkpoint,
+    save_checkpoint,
+};
+use crate::search::score::select;
+use crate::search::{AxisRotation, EdgeDensity, MoveGenerator, Scorer, Selection, expand_layer};
+use nalgebra::Point;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How duplicate candidates are detected by [`BeamSearch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dedup {
+    /// By graph hash within each layer, as [`crate::search::next_layer`].
+    Layer,
+    /// Within each layer, verifying hash collisions with an exact isomorphism test, as
+    /// [`crate::search::next_layer_checked`].
+    Checked,
+    /// By graph hash across all layers, so a graph met at an earlier depth is not expanded
+    /// again.
+    Global,
+}
+
+/// A scored candidate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scored<const D: usize> {
+    pub score: f64,
+    pub points: Vec<Point<f64, D>>,
+}
+
+/// What happened at one depth of a [`BeamSearch`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerReport<const D: usize> {
+    /// 1-based depth, the seed being at depth `0`.
+    pub depth: usize,
+    /// Number of distinct candidates generated at this depth.
+    pub generated: usize,
+    /// Number of candidates kept in the beam.
+    pub kept: usize,
+    /// The highest scoring candidates of the depth, best first, kept in the beam or not.
+    pub best: Vec<Scored<D>>,
+}
+
+/// Result of [`BeamSearch::run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeamResult<const D: usize> {
+    pub layers: Vec<LayerReport<D>>,
+    /// The beam after the last depth. The parent of a record is its id in the beam of the
+    /// previous depth, and its generator describes the move, see
+    /// [`MoveGenerator::generate_traced`].
+    pub frontier: Vec<LayerRecord<D>>,
+}
+
+/// Searches for point sets layer by layer, keeping only a beam of the most promising
+/// candidates of each layer to generate the next one.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::search::{GreedyColors, SearchConfig};
+///
+/// let config = SearchConfig::<2>::preset("triangle").unwrap();
+/// let result = config
+///     .beam_search(2)
+///     .with_scorer(&GreedyColors)
+///     .with_beam_width(10)
+///     .run(std::slice::from_ref(&config.seed));
+///
+/// // the Moser spindle is the best candidate at depth 2
+/// assert_eq!(result.layers[1].best[0].score, 4.0);
+/// ```
+pub struct BeamSearch<'a, const D: usize> {
+    depth: usize,
+    dist: f64,
+    widths: Vec<Option<usize>>,
+    scorer: &'a dyn Scorer,
+    selection: Selection,
+    moves: Vec<&'a dyn MoveGenerator<D>>,
+    dedup: Dedup,
+    report: usize,
+    checkpoint_interval: Duration,
+}
+
+impl<'a, const D: usize> BeamSearch<'a, D> {
+    /// A search of the given depth at [`DIST`], with [`AxisRotation`] moves, ranking by
+    /// [`EdgeDensity`], and keeping every candidate until a beam width is set.
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            dist: DIST,
+            widths: vec![None; depth],
+            scorer: &EdgeDensity,
+            selection: Selection::TopK,
+            moves: vec![&AxisRotation],
+            dedup: Dedup::Layer,
+            report: 5,
+            checkpoint_interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Uses an arbitrary distance to make edges.
+    pub fn with_dist(mut self, dist: f64) -> Self {
+        self.dist = dist;
+        self
+    }
+
+    /// Keeps at most `width` candidates at every depth.
+    pub fn with_beam_width(mut self, width: usize) -> Self {
+        self.widths = vec![Some(width); self.depth];
+        self
+    }
+
+    /// Sets the beam width of each depth, starting at depth `1`. [`None`] keeps the whole layer,
+    /// and missing depths keep the previous setting.
+    pub fn with_layer_widths(mut self, widths: &[Option<usize>]) -> Self {
+        for (width, &w) in self.widths.iter_mut().zip(widths) {
+            *width = w;
+        }
+        self
+    }
+
+    pub fn with_scorer(mut self, scorer: &'a dyn Scorer) -> Self {
+        self.scorer = scorer;
+        self
+    }
+
+    pub fn with_selection(mut self, selection: Selection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    pub fn with_moves(mut self, moves: &[&'a dyn MoveGenerator<D>]) -> Self {
+        self.moves = moves.to_vec();
+        self
+    }
+
+    pub fn with_dedup(mut self, dedup: Dedup) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Number of best candidates reported at each depth.
+    pub fn with_report(mut self, report: usize) -> Self {
+        self.report = report;
+        self
+    }
+
+    /// How often [`BeamSearch::resume`] saves its progress within a depth, a minute by default.
+    pub fn with_checkpoint_interval(mut self, interval: Duration) -> Self {
+        self.checkpoint_interval = interval;
+        self
+    }
+
+    /// Runs the search from the given seeds.
+    pub fn run(&self, seed: &[Vec<Point<f64, D>>]) -> BeamResult<D> {
+        self.search(self.start(seed), None)
+            .expect("no checkpoint is written")
+    }
+
+    /// Runs the search like [`BeamSearch::run`], saving its state to `path` after every depth
+    /// and every [`BeamSearch::with_checkpoint_interval`] within a depth, see
+    /// [`save_checkpoint`].
+    ///
+    /// If `path` already holds a checkpoint, the search restarts from it and `seed` is ignored,
+    /// so an interrupted search is resumed by running it again. The checkpoint is kept once
+    /// the search is done, and resuming it again only returns the result. A checkpoint written
+    /// by a search with other [`SearchSettings`] or another distance is rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ml_cnp::search::SearchConfig;
+    ///
+    /// let config = SearchConfig::<2>::preset("triangle").unwrap();
+    /// let path = std::env::temp_dir().join(format!("beam-{}.ckpt", std::process::id()));
+    /// let search = config.beam_search(2).with_beam_width(10);
+    /// let result = search.resume(std::slice::from_ref(&config.seed), &path).unwrap();
+    ///
+    /// // a finished checkpoint gives the same result
+    /// assert_eq!(search.resume(&[], &path).unwrap(), result);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn resume(
+        &self,
+        seed: &[Vec<Point<f64, D>>],
+        path: impl AsRef<Path>,
+    ) -> Result<BeamResult<D>, IoError> {
+        let path = path.as_ref();
+        let state = match load_checkpoint(path)? {
+            Some(checkpoint) if checkpoint.dist != self.dist => {
+                return Err(IoError::format(
+                    0,
+                    format!("the checkpoint was written at distance {}", checkpoint.dist),
+                ));
+            }
+            Some(checkpoint) => {
+                if let Some(setting) = checkpoint.settings.difference(&self.settings()) {
+                    return Err(IoError::format(
+                        0,
+                        format!("the checkpoint was written with another {setting}"),
+                    ));
+                }
+                checkpoint
+            }
+            None => self.start(seed),
+        };
+        self.search(state, Some(path))
+    }
+
+    /// The settings recorded in checkpoints.
+    fn settings(&self) -> SearchSettings {
+        SearchSettings {
+            depth: self.depth,
+            widths: self.widths.clone(),
+            dedup: self.dedup,
+            moves: self.moves.iter().map(|m| m.name().to_string()).collect(),
+            scorer: self.scorer.name().to_string(),
+            selection: self.selection,
+        }
+    }
+
+    /// The state of a search that has not started yet.
+    fn start(&self, seed: &[Vec<Point<f64, D>>]) -> Checkpoint<D> {
+        let frontier = layer_records(seed, self.dist, 0);
+        let seen = if self.dedup == Dedup::Global {
+            frontier.iter().map(|record| record.hash).collect()
+        } else {
+            vec![]
+        };
+        Checkpoint {
+            dist: self.dist,
+            settings: self.settings(),
+            depth: 1,
+            expanded: 0,
+            frontier,
+            candidates: vec![],
+            seen,
+            layers: vec![],
+        }
+    }
+
+    fn search(
+        &self,
+        mut state: Checkpoint<D>,
+        path: Option<&Path>,
+    ) -> Result<BeamResult<D>, IoError> {
+        let mut seen: HashSet<u64> = state.seen.iter().cloned().collect();
+        let mut last_save = Instant::now();
+
+        while state.depth <= self.depth {
+            // expand the frontier by chunks, to save progress within long depths
+            let mut index: HashMap<u64, Vec<usize>> = HashMap::new();
+            for (i, record) in state.candidates.iter().enumerate() {
+                index.entry(record.hash).or_default().push(i);
+            }
+            let chunk = 4 * rayon::current_num_threads();
+            while state.expanded < state.frontier.len() {
+                let end = (state.expanded + chunk).min(state.frontier.len());
+                self.expand(
+                    &state.frontier[state.expanded..end],
+                    &mut state.candidates,
+                    &mut index,
+                );
+                state.expanded = end;
+
+                if let Some(path) = path
+                    && last_save.elapsed() >= self.checkpoint_interval
+                {
+                    state.seen = seen.iter().cloned().collect();
+                    save_checkpoint(path, &state)?;
+                    last_save = Instant::now();
+                }
+            }
+
+            let candidates = std::mem::take(&mut state.candidates);
+            let candidates: Vec<_> = if self.dedup == Dedup::Global {
+                candidates
+                    .into_iter()
+                    .filter(|record| seen.insert(record.hash))
+                    .collect()
+            } else {
+                candidates
+            };
+            let graphs: Vec<_> = candidates
+                .par_iter()
+                .map(|record| self.graph(&record.points))
+                .collect();
+
+            let scores: Vec<_> = graphs.par_iter().map(|g| self.scorer.score(g)).collect();
+            let width = self.widths[state.depth - 1].unwrap_or(candidates.len());
+            let kept = select(&graphs, &scores, width, self.selection);
+
+            let mut best: Vec<_> = (0..candidates.len()).collect();
+            best.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+            let best = best
+                .into_iter()
+                .take(self.report)
+                .map(|i| Scored {
+                    score: scores[i],
+                    points: candidates[i].points.clone(),
+                })
+                .collect();
+
+            state.layers.push(LayerReport {
+                depth: state.depth,
+                generated: candidates.len(),
+                kept: kept.len(),
+                best,
+            });
+
+            let mut candidates: Vec<_> = candidates.into_iter().map(Some).collect();
+            state.frontier = kept
+                .into_iter()
+                .enumerate()
+                .map(|(id, i)| LayerRecord {
+                    id,
+                    ..candidates[i].take().unwrap()
+                })
+                .collect();
+            state.depth += 1;
+            state.expanded = 0;
+
+            if let Some(path) = path {
+                state.seen = seen.iter().cloned().collect();
+                save_checkpoint(path, &state)?;
+                last_save = Instant::now();
+            }
+        }
+
+        Ok(BeamResult {
+            layers: state.layers,
+            frontier: state.frontier,
+        })
+    }
+
+    /// Adds the distinct point sets generated from `parents` to `candidates`, whose indices are
+    /// listed in `index` by hash.
+    fn expand(
+        &self,
+        parents: &[LayerRecord<D>],
+        candidates: &mut Vec<LayerRecord<D>>,
+        index: &mut HashMap<u64, Vec<usize>>,
+    ) {
+        let points: Vec<_> = parents.iter().map(|record| record.points.clone()).collect();
+        let (layer, _) = expand_layer(
+            &points,
+            self.dist,
+            &self.moves,
+            self.dedup == Dedup::Checked,
+        );
+
+        for record in layer {
+            let same = index.entry(record.hash).or_default();
+            let duplicate = match self.dedup {
+                Dedup::Checked => {
+                    let graph = self.graph(&record.points);
+                    same.iter()
+                        .any(|&i| is_isomorphic(&self.graph(&candidates[i].points), &graph))
+                }
+                _ => !same.is_empty(),
+            };
+            if !duplicate {
+                same.push(candidates.len());
+                candidates.push(LayerRecord {
+                    id: candidates.len(),
+                    parent: record.parent.map(|i| parents[i].id),
+                    ..record
+                });
+            }
+        }
+    }
+
+    fn graph(&self, points: &[Point<f64, D>]) -> VecVecGraph {
+        VecVecGraph::from_points(points, self.dist)
+    }
+}
@@ -0,0 +1,318 @@
+use crate::constants::{DIST, INIT_POINTS, INIT_POINTS_2D};
+use crate::exact::Real;
+use crate::graph::VecVecGraph;
+use crate::search::{
+    AxisRotation, BeamSearch, CollisionStats, EdgeDensity, Mcts, MoveGenerator, Scorer, Selection,
+    next_layer_at, next_layer_checked_at, prune_layer_at,
+};
+use nalgebra::{Point, Point2};
+use std::fmt;
+
+/// An error met while parsing a [`SearchConfig`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// 1-based line number of the offending line, `0` for errors about the whole file.
+    pub line: usize,
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// The starting point of a search: a seed point set and the distance that makes an edge.
+///
+/// The seed is scaled so that the target distance becomes `1`, and the whole search runs at
+/// unit distance. `scale` remembers the original distance, to map results back to the
+/// original coordinates.
+///
+/// A configuration can be parsed from a small text format, so experiments do not need a
+/// recompilation:
+///
+/// ```text
+/// # lines starting with '#' are comments
+/// distance 2
+/// point 0 0 0
+/// point 2 0 0
+/// point 1 1.7320508075688772 0
+/// ```
+///
+/// Numbers can also be exact expressions without spaces, such as `(1+sqrt(3))/2`, so that
+/// published constructions can be pasted as they are.
+///
+/// Instead of `point` lines, `preset <name>` picks one of the built-in seeds: `tetrahedron`
+/// (3D), `triangle` or `moser_spindle` (2D). Presets come with their own distance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchConfig<const D: usize> {
+    pub seed: Vec<Point<f64, D>>,
+    pub dist: f64,
+    pub scale: f64,
+}
+
+impl<const D: usize> SearchConfig<D> {
+    /// Creates a configuration from a seed in original coordinates, normalizing it to unit
+    /// distance.
+    pub fn new(seed: Vec<Point<f64, D>>, dist: f64) -> Self {
+        assert!(dist > 0.0, "target distance must be positive");
+        Self {
+            seed: seed.iter().map(|p| p / dist).collect(),
+            dist: 1.0,
+            scale: dist,
+        }
+    }
+
+    /// Maps points of the search back to the coordinates the seed was given in.
+    pub fn denormalize(&self, points: &[Point<f64, D>]) -> Vec<Point<f64, D>> {
+        points.iter().map(|p| p * self.scale).collect()
+    }
+
+    /// Parses a configuration in the format described on [`SearchConfig`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ml_cnp::search::SearchConfig;
+    ///
+    /// let config = SearchConfig::<2>::parse("distance 2\npoint 0 0\npoint 2 0").unwrap();
+    /// assert_eq!(config.scale, 2.0);
+    /// assert_eq!(config.seed[1].x, 1.0);
+    /// ```
+    pub fn parse(desc: &str) -> Result<Self, ConfigError> {
+        let mut dist = None;
+        let mut preset = None;
+        let mut points = vec![];
+
+        for (i, line) in desc.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let key = parts.next().unwrap();
+            let args: Vec<&str> = parts.collect();
+            match key {
+                "distance" => {
+                    let [value] = args[..] else {
+                        return Err(ConfigError::new(line_no, "expected `distance <value>`"));
+                    };
+                    dist = Some(parse_f64(value, line_no)?);
+                }
+                "preset" => {
+                    let [name] = args[..] else {
+                        return Err(ConfigError::new(line_no, "expected `preset <name>`"));
+                    };
+                    preset = Some(
+                        Self::preset(name)
+                            .ok_or_else(|| ConfigError::new(line_no, unknown_preset(name)))?,
+                    );
+                }
+                "point" => {
+                    if args.len() != D {
+                        return Err(ConfigError::new(
+                            line_no,
+                            format!("expected {D} coordinates, found {}", args.len()),
+                        ));
+                    }
+                    let coords = args
+                        .iter()
+                        .map(|x| parse_f64(x, line_no))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    points.push(Point::from_slice(&coords));
+                }
+                _ => {
+                    return Err(ConfigError::new(
+                        line_no,
+                        format!("unknown directive `{key}`"),
+                    ));
+                }
+            }
+        }
+
+        match (preset, points.is_empty()) {
+            (Some(_), false) => Err(ConfigError::new(0, "a preset can not be mixed with points")),
+            (Some(_), true) if dist.is_some() => {
+                Err(ConfigError::new(0, "a preset comes with its own distance"))
+            }
+            (Some(preset), true) => Ok(preset),
+            (None, true) => Err(ConfigError::new(0, "no seed points given")),
+            (None, false) => {
+                let dist = dist.ok_or_else(|| ConfigError::new(0, "no distance given"))?;
+                if dist <= 0.0 {
+                    return Err(ConfigError::new(0, "distance must be positive"));
+                }
+                Ok(Self::new(points, dist))
+            }
+        }
+    }
+
+    /// A built-in seed of this dimension, by name.
+    pub fn preset(name: &str) -> Option<Self> {
+        let seed: Vec<Point<f64, D>> = match name {
+            "tetrahedron" => convert(&INIT_POINTS)?,
+            "triangle" => convert(&INIT_POINTS_2D)?,
+            "moser_spindle" => return convert(&moser_spindle()).map(|seed| Self::new(seed, 1.0)),
+            _ => return None,
+        };
+        Some(Self::new(seed, DIST))
+    }
+
+    /// Generates the next layer at the configured distance, see [`crate::search::next_layer`].
+    pub fn next_layer(&self, graphs: &[Vec<Point<f64, D>>]) -> Vec<Vec<Point<f64, D>>> {
+        next_layer_at(graphs, self.dist, &[&AxisRotation])
+    }
+
+    /// Generates the next layer with any combination of moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ml_cnp::search::{AxisRotation, Reflection, SearchConfig};
+    ///
+    /// let config = SearchConfig::<2>::preset("triangle").unwrap();
+    /// let seed = [config.seed.clone()];
+    /// let rotations = config.next_layer(&seed);
+    /// let both = config.next_layer_with(&seed, &[&AxisRotation, &Reflection]);
+    /// assert!(both.len() > rotations.len());
+    /// ```
+    pub fn next_layer_with(
+        &self,
+        graphs: &[Vec<Point<f64, D>>],
+        moves: &[&dyn MoveGenerator<D>],
+    ) -> Vec<Vec<Point<f64, D>>> {
+        next_layer_at(graphs, self.dist, moves)
+    }
+
+    /// See [`crate::search::next_layer_checked`].
+    pub fn next_layer_checked(
+        &self,
+        graphs: &[Vec<Point<f64, D>>],
+    ) -> (Vec<Vec<Point<f64, D>>>, CollisionStats) {
+        next_layer_checked_at(graphs, self.dist, &[&AxisRotation])
+    }
+
+    /// [`SearchConfig::next_layer_checked`] with any combination of moves.
+    pub fn next_layer_checked_with(
+        &self,
+        graphs: &[Vec<Point<f64, D>>],
+        moves: &[&dyn MoveGenerator<D>],
+    ) -> (Vec<Vec<Point<f64, D>>>, CollisionStats) {
+        next_layer_checked_at(graphs, self.dist, moves)
+    }
+
+    /// See [`crate::search::prune_layer`].
+    pub fn prune_layer(
+        &self,
+        graphs: Vec<Vec<Point<f64, D>>>,
+        target_size: usize,
+    ) -> Vec<Vec<Point<f64, D>>> {
+        prune_layer_at(
+            graphs,
+            target_size,
+            self.dist,
+            &EdgeDensity,
+            Selection::TopK,
+        )
+    }
+
+    /// See [`crate::search::prune_layer_by`].
+    pub fn prune_layer_by(
+        &self,
+        graphs: Vec<Vec<Point<f64, D>>>,
+        target_size: usize,
+        scorer: &dyn Scorer,
+        selection: Selection,
+    ) -> Vec<Vec<Point<f64, D>>> {
+        prune_layer_at(graphs, target_size, self.dist, scorer, selection)
+    }
+
+    /// A [`BeamSearch`] of the given depth at the configured distance.
+    pub fn beam_search<'a>(&self, depth: usize) -> BeamSearch<'a, D> {
+        BeamSearch::new(depth).with_dist(self.dist)
+    }
+
+    /// An [`Mcts`] looking for graphs that are not `colors`-colorable, at the configured
+    /// distance.
+    pub fn mcts<'a>(&self, colors: usize) -> Mcts<'a, D> {
+        Mcts::new(colors).with_dist(self.dist)
+    }
+
+    /// The unit-distance graph of a point set of the search.
+    pub fn graph(&self, points: &[Point<f64, D>]) -> VecVecGraph {
+        VecVecGraph::from_points(points, self.dist)
+    }
+}
+
+/// Dimension of the points of a configuration, so the caller can pick the matching
+/// `SearchConfig<D>`.
+pub fn config_dimension(desc: &str) -> Result<usize, ConfigError> {
+    for (i, line) in desc.lines().enumerate() {
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("point") => return Ok(parts.count()),
+            Some("preset") => {
+                return match parts.next() {
+                    Some("tetrahedron") => Ok(3),
+                    Some("triangle" | "moser_spindle") => Ok(2),
+                    name => Err(ConfigError::new(i + 1, unknown_preset(name.unwrap_or("")))),
+                };
+            }
+            _ => {}
+        }
+    }
+    Err(ConfigError::new(0, "no seed points given"))
+}
+
+fn unknown_preset(name: &str) -> String {
+    format!("unknown preset `{name}` for this dimension")
+}
+
+/// Parses a number, or an exact expression such as `sqrt(3)/2`.
+fn parse_f64(value: &str, line: usize) -> Result<f64, ConfigError> {
+    value
+        .parse()
+        .or_else(|_| value.parse::<Real>().map(|x| x.to_f64()))
+        .map_err(|_| ConfigError::new(line, format!("invalid number `{value}`")))
+}
+
+/// Converts points between dimensions, returning [`None`] if they differ.
+fn convert<const A: usize, const B: usize>(points: &[Point<f64, A>]) -> Option<Vec<Point<f64, B>>> {
+    (A == B).then(|| {
+        points
+            .iter()
+            .map(|p| Point::from_slice(p.coords.as_slice()))
+            .collect()
+    })
+}
+
+/// A unit-distance realization of the Moser spindle: two rhombi made of unit equilateral
+/// triangles, sharing a vertex, and rotated so that their far tips are at unit distance.
+pub fn moser_spindle() -> [Point2<f64>; 7] {
+    let h = 3f64.sqrt() / 2.0;
+    let tip = 3f64.sqrt();
+    // rotating the tip at distance √3 by φ moves it by 2√3·sin(φ/2) = 1
+    let phi = 2.0 * (1.0 / (2.0 * tip)).asin();
+    let rotate = |p: Point2<f64>| {
+        let (sin, cos) = phi.sin_cos();
+        Point2::new(p.x * cos - p.y * sin, p.x * sin + p.y * cos)
+    };
+
+    let a = Point2::new(h, 0.5);
+    let b = Point2::new(h, -0.5);
+    let c = Point2::new(tip, 0.0);
+    [Point2::origin(), a, b, c, rotate(a), rotate(b), rotate(c)]
+}
@@ -0,0 +1,334 @@
+use crate::algo::{ColorAlgorithm, HeuristicColoring, dsatur_colors};
+use crate::constants::DIST;
+use crate::graph::VecVecGraph;
+use crate::graph::hash::Hashable;
+use crate::rng::SplitMix64;
+use crate::search::{AxisRotation, MoveGenerator, Scored, for_each_candidate};
+use nalgebra::Point;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// A node of the search tree, i.e. a point set and the statistics of the rollouts through it.
+struct TreeNode<const D: usize> {
+    points: Vec<Point<f64, D>>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Candidates not yet added to the tree, [`None`] before the node is first expanded.
+    untried: Option<Vec<Vec<Point<f64, D>>>>,
+    visits: usize,
+    total_reward: f64,
+    /// Reward of the point set itself, without rollout.
+    reward: f64,
+}
+
+/// Point sets found not `k`-colorable, without duplicate graphs.
+#[derive(Default)]
+struct Uncolorable<const D: usize> {
+    hashes: HashSet<u64>,
+    points: Vec<Vec<Point<f64, D>>>,
+}
+
+/// Result of [`Mcts::run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MctsResult<const D: usize> {
+    /// The highest rewarded point sets added to the tree, best first.
+    pub best: Vec<Scored<D>>,
+    /// Point sets met in the tree or in rollouts whose graph is certainly not `k`-colorable.
+    pub uncolorable: Vec<Vec<Point<f64, D>>>,
+    /// Number of nodes of the final tree.
+    pub nodes: usize,
+}
+
+/// Monte Carlo tree search over the moves of the layer search.
+///
+/// Every iteration walks down the tree with the UCT rule, adds one new child, and plays a
+/// random sequence of moves from it. The reward of a rollout is the best reward of the point
+/// sets met on the way, which favors graphs that are hard or impossible to color with `k`
+/// colors:
+///
+/// * `1` if the graph is not `k`-colorable. This is only checked with the exact
+///   [`HeuristicColoring`] when [`crate::algo::dsatur`] needs more than `k` colors, and only
+///   for a short time, see [`Mcts::with_timeout`].
+/// * `c / (k + 2)` otherwise, where `c` is the number of colors used by `dsatur`, capped at
+///   `k + 1`. Graphs whose coloring timed out count as undecided, with `c = k + 1`.
+///
+/// Unlike [`crate::search::BeamSearch`], which commits to the best scoring candidates of each
+/// layer, the tree keeps revisiting construction paths whose rollouts did well.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::search::{Mcts, SearchConfig};
+///
+/// let config = SearchConfig::<2>::preset("triangle").unwrap();
+/// let result = config
+///     .mcts(3)
+///     .with_iterations(20)
+///     .run(&config.seed);
+///
+/// // the Moser spindle is found, as it is two moves away from the triangle
+/// assert!(!result.uncolorable.is_empty());
+/// ```
+pub struct Mcts<'a, const D: usize> {
+    colors: usize,
+    dist: f64,
+    moves: Vec<&'a dyn MoveGenerator<D>>,
+    iterations: usize,
+    rollout_depth: usize,
+    exploration: f64,
+    max_points: usize,
+    seed: u64,
+    report: usize,
+    timeout: Duration,
+}
+
+impl<'a, const D: usize> Mcts<'a, D> {
+    /// A search for graphs that are not `colors`-colorable, at [`DIST`], with [`AxisRotation`]
+    /// moves.
+    pub fn new(colors: usize) -> Self {
+        Self {
+            colors,
+            dist: DIST,
+            moves: vec![&AxisRotation],
+            iterations: 1000,
+            rollout_depth: 2,
+            exploration: std::f64::consts::SQRT_2,
+            max_points: 40,
+            seed: 0,
+            report: 5,
+            timeout: Duration::from_secs(1),
+        }
+    }
+
+    /// Uses an arbitrary distance to make edges.
+    pub fn with_dist(mut self, dist: f64) -> Self {
+        self.dist = dist;
+        self
+    }
+
+    pub fn with_moves(mut self, moves: &[&'a dyn MoveGenerator<D>]) -> Self {
+        self.moves = moves.to_vec();
+        self
+    }
+
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Number of random moves played from a new node.
+    pub fn with_rollout_depth(mut self, rollout_depth: usize) -> Self {
+        self.rollout_depth = rollout_depth;
+        self
+    }
+
+    /// The constant of the UCT rule, larger values favor less visited children.
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Candidates with more points are neither added to the tree nor played in rollouts.
+    pub fn with_max_points(mut self, max_points: usize) -> Self {
+        self.max_points = max_points;
+        self
+    }
+
+    /// Seed of the random generator, so runs are reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Number of best point sets reported.
+    pub fn with_report(mut self, report: usize) -> Self {
+        self.report = report;
+        self
+    }
+
+    /// How long the exact coloring of a single point set may take, one second by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Runs the search from a seed point set.
+    pub fn run(&self, seed: &[Point<f64, D>]) -> MctsResult<D> {
+        let mut rng = SplitMix64::new(self.seed);
+        let mut uncolorable = Uncolorable::default();
+        let mut seen = HashSet::new();
+
+        seen.insert(self.graph(seed).hash());
+        let (reward, _) = self.evaluate(seed, &mut uncolorable);
+        let mut tree = vec![TreeNode {
+            points: seed.to_vec(),
+            parent: None,
+            children: vec![],
+            untried: None,
+            visits: 0,
+            total_reward: 0.0,
+            reward,
+        }];
+
+        for _ in 0..self.iterations {
+            // selection
+            let mut node = 0;
+            loop {
+                if tree[node].untried.is_none() {
+                    let candidates = self.candidates(&tree[node].points, &mut seen);
+                    tree[node].untried = Some(candidates);
+                }
+                if !tree[node].untried.as_ref().unwrap().is_empty()
+                    || tree[node].children.is_empty()
+                {
+                    break;
+                }
+                node = self.select_child(&tree, node);
+            }
+
+            // expansion
+            let untried = tree[node].untried.as_mut().unwrap();
+            if !untried.is_empty() {
+                let points = untried.swap_remove(rng.below(untried.len()));
+                let (reward, _) = self.evaluate(&points, &mut uncolorable);
+                tree.push(TreeNode {
+                    points,
+                    parent: Some(node),
+                    children: vec![],
+                    untried: None,
+                    visits: 0,
+                    total_reward: 0.0,
+                    reward,
+                });
+                let child = tree.len() - 1;
+                tree[node].children.push(child);
+                node = child;
+            }
+
+            // simulation
+            let reward = self.rollout(&tree[node], &mut rng, &mut uncolorable);
+
+            // backpropagation
+            let mut current = Some(node);
+            while let Some(i) = current {
+                tree[i].visits += 1;
+                tree[i].total_reward += reward;
+                current = tree[i].parent;
+            }
+        }
+
+        let mut order: Vec<_> = (0..tree.len()).collect();
+        order.sort_by(|&a, &b| tree[b].reward.total_cmp(&tree[a].reward));
+        let best = order
+            .into_iter()
+            .take(self.report)
+            .map(|i| Scored {
+                score: tree[i].reward,
+                points: tree[i].points.clone(),
+            })
+            .collect();
+
+        MctsResult {
+            best,
+            uncolorable: uncolorable.points,
+            nodes: tree.len(),
+        }
+    }
+
+    /// The child with the highest UCT value.
+    fn select_child(&self, tree: &[TreeNode<D>], node: usize) -> usize {
+        let ln_visits = (tree[node].visits.max(1) as f64).ln();
+        let uct = |child: usize| {
+            let child = &tree[child];
+            if child.visits == 0 {
+                return f64::INFINITY;
+            }
+            let visits = child.visits as f64;
+            child.total_reward / visits + self.exploration * (ln_visits / visits).sqrt()
+        };
+
+        *tree[node]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| uct(a).total_cmp(&uct(b)))
+            .unwrap()
+    }
+
+    /// The new children of a node, skipping point sets whose graph is already in the tree.
+    fn candidates(
+        &self,
+        points: &[Point<f64, D>],
+        seen: &mut HashSet<u64>,
+    ) -> Vec<Vec<Point<f64, D>>> {
+        let mut res = vec![];
+        for_each_candidate(points, self.dist, &self.moves, |candidate, _| {
+            if candidate.len() <= self.max_points && seen.insert(self.graph(&candidate).hash()) {
+                res.push(candidate);
+            }
+        });
+        res
+    }
+
+    /// Plays random moves from a node, returning the best reward met.
+    fn rollout(
+        &self,
+        node: &TreeNode<D>,
+        rng: &mut SplitMix64,
+        uncolorable: &mut Uncolorable<D>,
+    ) -> f64 {
+        let mut best = node.reward;
+        let mut points = node.points.clone();
+        for _ in 0..self.rollout_depth {
+            // reservoir sampling of a single candidate
+            let mut chosen = None;
+            let mut count = 0;
+            for_each_candidate(&points, self.dist, &self.moves, |candidate, _| {
+                if candidate.len() <= self.max_points && candidate.len() > points.len() {
+                    count += 1;
+                    if rng.below(count) == 0 {
+                        chosen = Some(candidate);
+                    }
+                }
+            });
+            let Some(next) = chosen else {
+                break;
+            };
+
+            let (reward, certain) = self.evaluate(&next, uncolorable);
+            best = best.max(reward);
+            points = next;
+            if certain {
+                break;
+            }
+        }
+        best
+    }
+
+    /// The reward of a point set, and whether it is certainly not `k`-colorable, in which case
+    /// it is recorded.
+    fn evaluate(&self, points: &[Point<f64, D>], uncolorable: &mut Uncolorable<D>) -> (f64, bool) {
+        let graph = self.graph(points);
+        let used = dsatur_colors(&graph);
+        if used <= self.colors {
+            return (used as f64 / (self.colors + 2) as f64, false);
+        }
+
+        let hash = graph.hash();
+        if uncolorable.hashes.contains(&hash) {
+            return (1.0, true);
+        }
+        let mut algo =
+            HeuristicColoring::create(self.colors, Rc::new(graph)).with_timeout(self.timeout);
+        if algo.color().is_none() && !algo.timed_out() {
+            uncolorable.hashes.insert(hash);
+            uncolorable.points.push(points.to_vec());
+            return (1.0, true);
+        }
+        ((self.colors + 1) as f64 / (self.colors + 2) as f64, false)
+    }
+
+    fn graph(&self, points: &[Point<f64, D>]) -> VecVecGraph {
+        VecVecGraph::from_points(points, self.dist)
+    }
+}
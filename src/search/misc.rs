@@ -1,26 +1,59 @@
-use crate::linalg::point_to_line_proj;
-use nalgebra::Point3;
+use crate::constants::EPS;
+use crate::linalg::point_to_flat_proj;
+use crate::spatial::SpatialHash;
+use approx::AbsDiffEq;
+use nalgebra::Point;
 
-/// Calculates the rotation angle required to rotate a point around an axis
-/// defined by two points, such that the point reaches a specified distance from its original position.
+/// Calculates the rotation angle required to rotate a point around a flat (an axis in 3D,
+/// a point in 2D) defined by some points, such that the point reaches a specified distance
+/// from its original position.
 ///
 /// # Arguments
 ///
-/// * `original` - A reference to the `Point3<f64>` representing the original position of the point.
-/// * `axis_start` - A reference to the `Point3<f64>` marking the start of the rotation axis.
-/// * `axis_end` - A reference to the `Point3<f64>` marking the end of the rotation axis.
+/// * `original` - A reference to the `Point<f64, D>` representing the original position of the point.
+/// * `flat` - The points spanning the flat that the rotation fixes.
 /// * `target_dist` - The target distance from the original position after rotation.
 ///
 /// # Returns
 ///
-/// * The rotation angle in radians as `f64`.
-pub fn get_rotate_angle(
-    original: &Point3<f64>,
-    axis_start: &Point3<f64>,
-    axis_end: &Point3<f64>,
+/// * The rotation angle in radians as `f64`, which is NaN if the point is closer than
+///   `target_dist / 2` to the flat.
+pub fn get_rotate_angle<const D: usize>(
+    original: &Point<f64, D>,
+    flat: &[Point<f64, D>],
     target_dist: f64,
 ) -> f64 {
-    let proj = point_to_line_proj(original, axis_start, axis_end);
+    let proj = point_to_flat_proj(original, flat);
     let dist = (proj - original).norm();
     (target_dist / 2.0 / dist).asin() * 2.0
 }
+
+/// All `k`-subsets of `0..n`, as increasing index vectors in lexicographic order.
+pub fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    (k - 1..n)
+        .flat_map(|last| {
+            combinations(last, k - 1).into_iter().map(move |mut c| {
+                c.push(last);
+                c
+            })
+        })
+        .collect()
+}
+
+/// Removes points that coincide with an earlier point within `EPS`, keeping the order.
+pub fn dedup_points<const D: usize>(points: &[Point<f64, D>]) -> Vec<Point<f64, D>> {
+    let index = SpatialHash::new(points, EPS);
+    points
+        .iter()
+        .enumerate()
+        .filter(|(i, p)| {
+            !index
+                .candidates(p)
+                .any(|j| j < *i && points[j].abs_diff_eq(p, EPS))
+        })
+        .map(|(_, p)| *p)
+        .collect()
+}
@@ -0,0 +1,221 @@
+use crate::constants::EPS;
+use crate::linalg::FlatRotation;
+use crate::search::misc::{combinations, dedup_points};
+use crate::search::{AxisMove, merge};
+use crate::spatial::SpatialHash;
+use approx::AbsDiffEq;
+use nalgebra::Point;
+
+/// A way of building new point sets from a point set of the current layer.
+///
+/// [`crate::search::next_layer`] only uses [`AxisRotation`]. Other generators can be combined
+/// with it through [`crate::search::SearchConfig::next_layer_with`].
+pub trait MoveGenerator<const D: usize>: Sync {
+    /// A short name of the move, for reports.
+    fn name(&self) -> &str;
+
+    /// Calls `f` with every point set obtained from `points` by one application of the move,
+    /// where `dist` is the distance that makes an edge.
+    fn generate(&self, points: &[Point<f64, D>], dist: f64, f: &mut dyn FnMut(Vec<Point<f64, D>>));
+
+    /// Same as [`MoveGenerator::generate`], also passing a description of every move, which
+    /// is kept as the generator of the point set in layer files. It is the name of the move
+    /// unless a generator describes its moves more precisely.
+    fn generate_traced(
+        &self,
+        points: &[Point<f64, D>],
+        dist: f64,
+        f: &mut dyn FnMut(Vec<Point<f64, D>>, &str),
+    ) {
+        self.generate(points, dist, &mut |candidate| f(candidate, self.name()));
+    }
+}
+
+/// Rotates the whole set around the flat spanned by `D - 1` of its points (an axis through two
+/// points in 3D, a single point in the plane), by the angle that moves a third point to unit
+/// distance from itself, and merges the rotated copy with the original.
+///
+/// Every single move is an [`AxisMove`], described as such in layer files.
+pub struct AxisRotation;
+
+impl AxisRotation {
+    /// The moves that may apply to `points`, in the order they are generated. Some of them do
+    /// not apply when the target is too close to the flat, see [`AxisMove::apply`].
+    pub fn moves<const D: usize>(points: &[Point<f64, D>]) -> Vec<AxisMove> {
+        let mut moves = vec![];
+        for flat in combinations(points.len(), D - 1) {
+            for (target, p) in points.iter().enumerate() {
+                if flat.iter().any(|&i| p.abs_diff_eq(&points[i], EPS)) {
+                    continue;
+                }
+                for positive in [true, false] {
+                    moves.push(AxisMove {
+                        flat: flat.clone(),
+                        target,
+                        positive,
+                    });
+                }
+            }
+        }
+        moves
+    }
+}
+
+impl<const D: usize> MoveGenerator<D> for AxisRotation {
+    fn name(&self) -> &str {
+        "axis-rotation"
+    }
+
+    fn generate(&self, points: &[Point<f64, D>], dist: f64, f: &mut dyn FnMut(Vec<Point<f64, D>>)) {
+        for step in Self::moves(points) {
+            if let Some(candidate) = step.apply(points, dist) {
+                f(candidate);
+            }
+        }
+    }
+
+    fn generate_traced(
+        &self,
+        points: &[Point<f64, D>],
+        dist: f64,
+        f: &mut dyn FnMut(Vec<Point<f64, D>>, &str),
+    ) {
+        for step in Self::moves(points) {
+            if let Some(candidate) = step.apply(points, dist) {
+                f(candidate, &step.to_string());
+            }
+        }
+    }
+}
+
+/// Rotates the whole set around one of its points `c`, in the plane of `c` and an edge `ab`
+/// with `|ca| = |cb|`, so that `a` is mapped onto its neighbor `b`. The rotated copy shares
+/// `c` and `b` with the original.
+pub struct PivotRotation;
+
+impl<const D: usize> MoveGenerator<D> for PivotRotation {
+    fn name(&self) -> &str {
+        "pivot-rotation"
+    }
+
+    fn generate(&self, points: &[Point<f64, D>], dist: f64, f: &mut dyn FnMut(Vec<Point<f64, D>>)) {
+        let index = SpatialHash::new(points, dist);
+        for (i, a) in points.iter().enumerate() {
+            for j in index.at_distance(a, dist) {
+                for (k, c) in points.iter().enumerate() {
+                    if k == i || k == j {
+                        continue;
+                    }
+                    if let Some(rotation) = FlatRotation::mapping(c, a, &points[j]) {
+                        let rotated: Vec<_> = points.iter().map(|p| rotation.apply(p)).collect();
+                        f(merge(&rotated, points));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reflects the whole set in the perpendicular bisector of two of its points, which swaps
+/// them, and merges the mirror image with the original.
+pub struct Reflection;
+
+impl<const D: usize> MoveGenerator<D> for Reflection {
+    fn name(&self) -> &str {
+        "reflection"
+    }
+
+    fn generate(&self, points: &[Point<f64, D>], _: f64, f: &mut dyn FnMut(Vec<Point<f64, D>>)) {
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                let normal = points[j] - points[i];
+                if normal.norm() < EPS {
+                    continue;
+                }
+                let normal = normal.normalize();
+                let middle = points[i] + (points[j] - points[i]) / 2.0;
+
+                let reflected: Vec<_> = points
+                    .iter()
+                    .map(|p| p - normal * (2.0 * (p - middle).dot(&normal)))
+                    .collect();
+                f(merge(&reflected, points));
+            }
+        }
+    }
+}
+
+/// The Minkowski sum `S + T = {s + t}` with a fixed unit-distance set `T`, translated so that
+/// its first point is the origin and `S` is a subset of the sum.
+///
+/// Whenever `s1 s2` is an edge of `S` and `t1 t2` one of `T`, `s1 + t` and `s2 + t` as well as
+/// `s + t1` and `s + t2` are edges of the sum, so its graph contains the Cartesian product of
+/// the two graphs, as in de Grey's construction.
+pub struct MinkowskiSum<const D: usize> {
+    pub summand: Vec<Point<f64, D>>,
+}
+
+impl<const D: usize> MoveGenerator<D> for MinkowskiSum<D> {
+    fn name(&self) -> &str {
+        "minkowski-sum"
+    }
+
+    fn generate(&self, points: &[Point<f64, D>], _: f64, f: &mut dyn FnMut(Vec<Point<f64, D>>)) {
+        let Some(origin) = self.summand.first() else {
+            return;
+        };
+        let sum: Vec<_> = points
+            .iter()
+            .flat_map(|s| self.summand.iter().map(move |t| s + (t - origin)))
+            .collect();
+        f(dedup_points(&sum));
+    }
+}
+
+/// Unites the set with a rotated copy of a different set `T`.
+///
+/// A point `o` of `T` is glued onto a point `s` of the set. The copy is then rotated around
+/// a flat through `s` spanned with `D - 2` neighbors of `s`, so that a neighbor of `s` in
+/// the copy lands at unit distance from some point of the set. Only neighbors are used to keep
+/// the number of candidates manageable.
+pub struct UnionWith<const D: usize> {
+    pub other: Vec<Point<f64, D>>,
+}
+
+impl<const D: usize> MoveGenerator<D> for UnionWith<D> {
+    fn name(&self) -> &str {
+        "union-with"
+    }
+
+    fn generate(&self, points: &[Point<f64, D>], dist: f64, f: &mut dyn FnMut(Vec<Point<f64, D>>)) {
+        let index = SpatialHash::new(points, dist);
+        for s in points {
+            let neighbors: Vec<_> = index.at_distance(s, dist).collect();
+            for o in &self.other {
+                let copy: Vec<_> = self.other.iter().map(|q| s + (q - o)).collect();
+                let copy_neighbors: Vec<_> = copy
+                    .iter()
+                    .filter(|q| (*q - s).norm().abs_diff_eq(&dist, EPS))
+                    .collect();
+
+                for indices in combinations(neighbors.len(), D - 2) {
+                    let flat: Vec<_> = std::iter::once(*s)
+                        .chain(indices.iter().map(|&i| points[neighbors[i]]))
+                        .collect();
+                    for q in &copy_neighbors {
+                        for p in points {
+                            for angle in FlatRotation::angles_to_distance(&flat, q, p, dist) {
+                                let Some(rotation) = FlatRotation::new(&flat, angle) else {
+                                    continue;
+                                };
+                                let rotated: Vec<_> =
+                                    copy.iter().map(|q| rotation.apply(q)).collect();
+                                f(merge(points, &rotated));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use crate::graph::hash::Hashable;
+use crate::graph::{Graph, VecVecGraph};
+use crate::io::LayerRecord;
+use crate::linalg::FlatRotation;
+use crate::search::misc::get_rotate_angle;
+use crate::search::{AxisRotation, expand_layer, merge};
+use nalgebra::Point;
+use std::fmt;
+use std::str::FromStr;
+
+/// One move of [`crate::search::AxisRotation`]: the parent set is rotated around the flat
+/// spanned by its points `flat` (the axis `i, j` in 3D), by the angle that moves its point
+/// `target` to unit distance, and merged with the rotated copy.
+///
+/// It is written `axis-rotation(i,j;p;+)`, the sign being that of the angle.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AxisMove {
+    pub flat: Vec<usize>,
+    pub target: usize,
+    pub positive: bool,
+}
+
+impl AxisMove {
+    /// Applies the move to `points`, or returns [`None`] if the indices are out of range or
+    /// the rotation does not exist.
+    pub fn apply<const D: usize>(
+        &self,
+        points: &[Point<f64, D>],
+        dist: f64,
+    ) -> Option<Vec<Point<f64, D>>> {
+        let target = points.get(self.target)?;
+        let flat = self
+            .flat
+            .iter()
+            .map(|&i| points.get(i).cloned())
+            .collect::<Option<Vec<_>>>()?;
+        let angle = get_rotate_angle(target, &flat, dist);
+        if !angle.is_finite() {
+            return None;
+        }
+        let rotation = FlatRotation::new(&flat, if self.positive { angle } else { -angle })?;
+        let rotated: Vec<_> = points.iter().map(|p| rotation.apply(p)).collect();
+        Some(merge(&rotated, points))
+    }
+}
+
+impl fmt::Display for AxisMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flat: Vec<String> = self.flat.iter().map(|i| i.to_string()).collect();
+        let sign = if self.positive { '+' } else { '-' };
+        write!(
+            f,
+            "axis-rotation({};{};{sign})",
+            flat.join(","),
+            self.target
+        )
+    }
+}
+
+impl FromStr for AxisMove {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid move `{s}`");
+        let args = s
+            .strip_prefix("axis-rotation(")
+            .and_then(|s| s.strip_suffix(')'))
+            .ok_or_else(invalid)?;
+        let [flat, target, sign] = args.split(';').collect::<Vec<_>>()[..] else {
+            return Err(invalid());
+        };
+        let flat = if flat.is_empty() {
+            vec![]
+        } else {
+            flat.split(',')
+                .map(|i| i.parse().map_err(|_| invalid()))
+                .collect::<Result<_, _>>()?
+        };
+        let target = target.parse().map_err(|_| invalid())?;
+        let positive = match sign {
+            "+" => true,
+            "-" => false,
+            _ => return Err(invalid()),
+        };
+        Ok(Self {
+            flat,
+            target,
+            positive,
+        })
+    }
+}
+
+/// Where a point set of a [`Lineage`] comes from: the index of its parent in the previous
+/// layer and the move applied to it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Provenance {
+    pub parent: usize,
+    pub step: AxisMove,
+}
+
+/// A point set with its provenance, [`None`] for a seed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Traced<const D: usize> {
+    pub points: Vec<Point<f64, D>>,
+    /// The hash of the unit distance graph.
+    pub hash: u64,
+    pub provenance: Option<Provenance>,
+}
+
+/// Same as [`crate::search::next_layer`] at distance `dist`, with the provenance of every
+/// point set. Among the moves that give the same graph, the first one applied to the first
+/// parent is kept, so the result does not depend on the order of the parallel search.
+pub fn next_layer_traced<const D: usize>(
+    graphs: &[Vec<Point<f64, D>>],
+    dist: f64,
+) -> Vec<Traced<D>> {
+    let (records, _) = expand_layer(graphs, dist, &[&AxisRotation], false);
+    records
+        .into_iter()
+        .map(|record| Traced {
+            provenance: Some(Provenance {
+                parent: record.parent.unwrap(),
+                step: record
+                    .generator
+                    .unwrap()
+                    .parse()
+                    .expect("axis rotations are described by their move"),
+            }),
+            points: record.points,
+            hash: record.hash,
+        })
+        .collect()
+}
+
+/// A sequence of moves building a point set from a seed, see [`Lineage::chain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Construction {
+    /// The index of the seed in the first layer.
+    pub seed: usize,
+    pub moves: Vec<AxisMove>,
+}
+
+impl Construction {
+    /// Applies the moves to the seed, or returns [`None`] if one of them does not apply.
+    pub fn replay<const D: usize>(
+        &self,
+        seeds: &[Vec<Point<f64, D>>],
+        dist: f64,
+    ) -> Option<Vec<Point<f64, D>>> {
+        let mut points = seeds.get(self.seed)?.clone();
+        for step in &self.moves {
+            points = step.apply(&points, dist)?;
+        }
+        Some(points)
+    }
+
+    /// Describes the construction step by step, with the size of the graph after every move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ml_cnp::search::{Lineage, SearchConfig};
+    ///
+    /// let config = SearchConfig::<2>::preset("triangle").unwrap();
+    /// let mut lineage = Lineage::new(vec![config.seed.clone()], config.dist);
+    /// lineage.grow();
+    /// lineage.grow();
+    ///
+    /// let explanation = lineage.chain(2, 0).explain(lineage.seeds(), config.dist).unwrap();
+    /// assert!(explanation.starts_with("seed 0: 3 points, 3 edges\n1. axis-rotation("));
+    /// ```
+    pub fn explain<const D: usize>(
+        &self,
+        seeds: &[Vec<Point<f64, D>>],
+        dist: f64,
+    ) -> Option<String> {
+        let describe = |points: &[Point<f64, D>]| {
+            let graph = VecVecGraph::from_points(points, dist);
+            let edges = (0..graph.size())
+                .map(|v| graph.neighbors(v).len())
+                .sum::<usize>()
+                / 2;
+            format!("{} points, {edges} edges", points.len())
+        };
+
+        let mut points = seeds.get(self.seed)?.clone();
+        let mut lines = vec![format!("seed {}: {}", self.seed, describe(&points))];
+        for (i, step) in self.moves.iter().enumerate() {
+            points = step.apply(&points, dist)?;
+            lines.push(format!("{}. {step}: {}", i + 1, describe(&points)));
+        }
+        Some(lines.join("\n"))
+    }
+}
+
+impl fmt::Display for Construction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seed {}", self.seed)?;
+        for step in &self.moves {
+            write!(f, " -> {step}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The layers of a search with the provenance of every point set, to replay and explain how
+/// a point set was built.
+///
+/// # Examples
+///
+/// ```
+/// # use ml_cnp::search::{Lineage, SearchConfig};
+///
+/// let config = SearchConfig::<2>::preset("triangle").unwrap();
+/// let mut lineage = Lineage::new(vec![config.seed.clone()], config.dist);
+/// lineage.grow();
+/// lineage.grow();
+///
+/// // every point set is rebuilt from the seed by its chain of moves
+/// let last = &lineage.layers()[2];
+/// let construction = lineage.chain(2, last.len() - 1);
+/// assert_eq!(construction.moves.len(), 2);
+/// let replayed = construction.replay(lineage.seeds(), config.dist).unwrap();
+/// assert_eq!(replayed, last[last.len() - 1].points);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lineage<const D: usize> {
+    dist: f64,
+    seeds: Vec<Vec<Point<f64, D>>>,
+    layers: Vec<Vec<Traced<D>>>,
+}
+
+impl<const D: usize> Lineage<D> {
+    pub fn new(seeds: Vec<Vec<Point<f64, D>>>, dist: f64) -> Self {
+        let first = seeds
+            .iter()
+            .map(|points| Traced {
+                points: points.clone(),
+                hash: VecVecGraph::from_points(points, dist).hash(),
+                provenance: None,
+            })
+            .collect();
+        Self {
+            dist,
+            seeds,
+            layers: vec![first],
+        }
+    }
+
+    pub fn seeds(&self) -> &[Vec<Point<f64, D>>] {
+        &self.seeds
+    }
+
+    /// The layers, starting with the seeds.
+    pub fn layers(&self) -> &[Vec<Traced<D>>] {
+        &self.layers
+    }
+
+    /// The next layer of the last one, see [`next_layer_traced`]. It can be pruned before
+    /// [`Lineage::push`].
+    pub fn next_layer(&self) -> Vec<Traced<D>> {
+        let last: Vec<_> = self
+            .layers
+            .last()
+            .unwrap()
+            .iter()
+            .map(|traced| traced.points.clone())
+            .collect();
+        next_layer_traced(&last, self.dist)
+    }
+
+    /// Adds a layer, whose parents are indices in the last layer.
+    pub fn push(&mut self, layer: Vec<Traced<D>>) {
+        let parents = self.layers.last().unwrap().len();
+        assert!(
+            layer
+                .iter()
+                .all(|t| t.provenance.as_ref().is_some_and(|p| p.parent < parents)),
+            "every point set must have a parent in the last layer"
+        );
+        self.layers.push(layer);
+    }
+
+    /// Adds the whole next layer.
+    pub fn grow(&mut self) {
+        let layer = self.next_layer();
+        self.push(layer);
+    }
+
+    /// The moves building the point set `index` of layer `depth` from its seed.
+    pub fn chain(&self, depth: usize, index: usize) -> Construction {
+        let mut moves = vec![];
+        let mut index = index;
+        for layer in self.layers[1..=depth].iter().rev() {
+            let provenance = layer[index].provenance.as_ref().unwrap();
+            moves.push(provenance.step.clone());
+            index = provenance.parent;
+        }
+        moves.reverse();
+        Construction { seed: index, moves }
+    }
+
+    /// The shortest construction of a point set with the same graph as `points`, if any was
+    /// met: the one of its first occurrence in the layers.
+    pub fn minimal_chain(&self, points: &[Point<f64, D>]) -> Option<Construction> {
+        let hash = VecVecGraph::from_points(points, self.dist).hash();
+        self.layers.iter().enumerate().find_map(|(depth, layer)| {
+            let index = layer.iter().position(|traced| traced.hash == hash)?;
+            Some(self.chain(depth, index))
+        })
+    }
+
+    /// All point sets as records for [`crate::io::save_layer`], numbered layer by layer, with
+    /// their parent and move.
+    pub fn records(&self) -> Vec<LayerRecord<D>> {
+        let mut records = vec![];
+        let mut offset = 0;
+        let mut parent_offset = 0;
+        for layer in &self.layers {
+            for (i, traced) in layer.iter().enumerate() {
+                records.push(LayerRecord {
+                    id: offset + i,
+                    parent: traced.provenance.as_ref().map(|p| parent_offset + p.parent),
+                    generator: traced.provenance.as_ref().map(|p| p.step.to_string()),
+                    hash: traced.hash,
+                    points: traced.points.clone(),
+                });
+            }
+            parent_offset = offset;
+            offset += layer.len();
+        }
+        records
+    }
+}
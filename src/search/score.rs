@@ -0,0 +1,203 @@
+use crate::algo::dsatur_colors;
+use crate::graph::clique::count_cliques;
+use crate::graph::fractional::fractional_chromatic;
+use crate::graph::hash::WLHasher;
+use crate::graph::spectral::Spectrum;
+use crate::graph::theta::lovasz_theta_complement;
+use crate::graph::{Graph, VecVecGraph};
+use std::collections::HashMap;
+
+/// Ranks candidate graphs of a layer, higher scores being more promising.
+pub trait Scorer: Sync {
+    /// A short name of the score, for reports.
+    fn name(&self) -> &str;
+
+    fn score(&self, graph: &VecVecGraph) -> f64;
+}
+
+/// Number of edges per node.
+pub struct EdgeDensity;
+
+impl Scorer for EdgeDensity {
+    fn name(&self) -> &str {
+        "edge-density"
+    }
+
+    fn score(&self, graph: &VecVecGraph) -> f64 {
+        if graph.size() == 0 {
+            return 0.0;
+        }
+        // `edges` lists both directions of every edge
+        graph.edges().len() as f64 / 2.0 / graph.size() as f64
+    }
+}
+
+/// The smallest degree. A `k`-critical graph has minimum degree at least `k - 1`, so
+/// candidates with low degree nodes can not be minimal obstructions to `k`-coloring.
+pub struct MinDegree;
+
+impl Scorer for MinDegree {
+    fn name(&self) -> &str {
+        "min-degree"
+    }
+
+    fn score(&self, graph: &VecVecGraph) -> f64 {
+        (0..graph.size())
+            .map(|node| graph.neighbors(node).len())
+            .min()
+            .unwrap_or(0) as f64
+    }
+}
+
+/// Number of cliques of a given size, e.g. triangles or `K4`s.
+pub struct CliqueCount {
+    pub size: usize,
+}
+
+impl Scorer for CliqueCount {
+    fn name(&self) -> &str {
+        match self.size {
+            3 => "triangles",
+            4 => "k4s",
+            _ => "cliques",
+        }
+    }
+
+    fn score(&self, graph: &VecVecGraph) -> f64 {
+        count_cliques(graph, self.size) as f64
+    }
+}
+
+/// Number of colors used by the greedy [`crate::algo::dsatur`] coloring. It is an upper bound
+/// on the chromatic number, and graphs that defeat the greedy coloring tend to be hard ones.
+pub struct GreedyColors;
+
+impl Scorer for GreedyColors {
+    fn name(&self) -> &str {
+        "greedy-colors"
+    }
+
+    fn score(&self, graph: &VecVecGraph) -> f64 {
+        dsatur_colors(graph) as f64
+    }
+}
+
+/// The best spectral lower bound on the chromatic number, see
+/// [`Spectrum::best_lower_bound`]. It is left unrounded to break ties between graphs with the
+/// same bound.
+pub struct SpectralBound;
+
+impl Scorer for SpectralBound {
+    fn name(&self) -> &str {
+        "spectral-bound"
+    }
+
+    fn score(&self, graph: &VecVecGraph) -> f64 {
+        Spectrum::new(graph).best_lower_bound()
+    }
+}
+
+/// The fractional chromatic number, a lower bound on the chromatic number computed by linear
+/// programming, see [`fractional_chromatic`]. It is much slower than the other scores.
+pub struct FractionalChromatic;
+
+impl Scorer for FractionalChromatic {
+    fn name(&self) -> &str {
+        "fractional-chromatic"
+    }
+
+    fn score(&self, graph: &VecVecGraph) -> f64 {
+        fractional_chromatic(graph).map_or(0.0, |coloring| coloring.value)
+    }
+}
+
+/// The Lovász theta of the complement, a lower bound on the chromatic number between the
+/// spectral bounds and the fractional chromatic number, see [`lovasz_theta_complement`].
+pub struct ThetaBound;
+
+impl Scorer for ThetaBound {
+    fn name(&self) -> &str {
+        "theta"
+    }
+
+    fn score(&self, graph: &VecVecGraph) -> f64 {
+        lovasz_theta_complement(graph).value
+    }
+}
+
+/// How the candidates are picked once they are scored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Selection {
+    /// The candidates with the highest scores.
+    TopK,
+    /// Candidates in order of decreasing score, skipping those whose Weisfeiler-Lehman label
+    /// histogram has a similarity above `max_similarity` (between `0` and `1`) with an already
+    /// selected one. Skipped candidates fill the remaining slots if there are too few others.
+    Diverse { max_similarity: f64 },
+}
+
+/// Weighted Jaccard similarity of the WL label multisets of two graphs.
+fn similarity(a: &HashMap<u64, usize>, b: &HashMap<u64, usize>) -> f64 {
+    let (mut min, mut max) = (0, 0);
+    for (label, &x) in a {
+        let y = b.get(label).cloned().unwrap_or(0);
+        min += x.min(y);
+        max += x.max(y);
+    }
+    max += b
+        .iter()
+        .filter(|(label, _)| !a.contains_key(label))
+        .map(|(_, &y)| y)
+        .sum::<usize>();
+
+    if max == 0 {
+        1.0
+    } else {
+        min as f64 / max as f64
+    }
+}
+
+fn label_histogram(graph: &VecVecGraph) -> HashMap<u64, usize> {
+    let mut histogram = HashMap::new();
+    for label in WLHasher::default().labels(graph) {
+        *histogram.entry(label).or_default() += 1;
+    }
+    histogram
+}
+
+/// Picks `target_size` indices among the graphs, given their scores, according to `selection`.
+pub(crate) fn select(
+    graphs: &[VecVecGraph],
+    scores: &[f64],
+    target_size: usize,
+    selection: Selection,
+) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..graphs.len()).collect();
+    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+
+    let Selection::Diverse { max_similarity } = selection else {
+        order.truncate(target_size);
+        return order;
+    };
+
+    let histograms: Vec<_> = graphs.iter().map(label_histogram).collect();
+    let mut selected: Vec<usize> = vec![];
+    let mut skipped = vec![];
+    for i in order {
+        if selected.len() == target_size {
+            break;
+        }
+        if selected
+            .iter()
+            .any(|&j| similarity(&histograms[i], &histograms[j]) > max_similarity)
+        {
+            skipped.push(i);
+        } else {
+            selected.push(i);
+        }
+    }
+
+    let missing = target_size.saturating_sub(selected.len());
+    selected.extend(skipped.into_iter().take(missing));
+    selected
+}
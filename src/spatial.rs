@@ -0,0 +1,133 @@
+use crate::constants::EPS;
+use approx::AbsDiffEq;
+use nalgebra::Point;
+use std::collections::HashMap;
+
+/// A uniform grid over a set of points for fast fixed-radius queries.
+///
+/// Points are bucketed into cubic cells whose side is at least the query distance, so every
+/// point at distance `dist` from a given point lies in one of the `3^D` surrounding cells. For
+/// point sets of roughly uniform density this finds all pairs at a given distance in time
+/// linear in the number of points and pairs, instead of comparing every pair.
+///
+/// # Examples
+///
+/// ```
+/// # use nalgebra::Point3;
+/// # use ml_cnp::spatial::SpatialHash;
+///
+/// let points = vec![
+///     Point3::new(0.0, 0.0, 0.0),
+///     Point3::new(1.0, 0.0, 0.0),
+///     Point3::new(5.0, 0.0, 0.0),
+/// ];
+/// let index = SpatialHash::new(&points, 1.0);
+/// assert_eq!(index.pairs_at_distance(1.0), vec![(1, 0)]);
+/// ```
+pub struct SpatialHash<'a, const D: usize> {
+    points: &'a [Point<f64, D>],
+    cell_size: f64,
+    cells: HashMap<[i64; D], Vec<usize>>,
+}
+
+impl<'a, const D: usize> SpatialHash<'a, D> {
+    /// Builds the index. `dist` is the largest distance that will be queried.
+    pub fn new(points: &'a [Point<f64, D>], dist: f64) -> Self {
+        let cell_size = dist + EPS;
+        let mut cells: HashMap<[i64; D], Vec<usize>> = HashMap::new();
+        for (i, p) in points.iter().enumerate() {
+            cells.entry(cell_of(p, cell_size)).or_default().push(i);
+        }
+
+        Self {
+            points,
+            cell_size,
+            cells,
+        }
+    }
+
+    /// Indices of the indexed points in the `3^D` cells around `point`. This includes every
+    /// point within the distance the index was built for, and possibly some further ones.
+    pub fn candidates<'s>(
+        &'s self,
+        point: &Point<f64, D>,
+    ) -> impl Iterator<Item = usize> + use<'s, 'a, D> {
+        let center = cell_of(point, self.cell_size);
+
+        (0..3usize.pow(D as u32))
+            .filter_map(move |mut offset| {
+                let mut cell = center;
+                for c in cell.iter_mut() {
+                    *c += (offset % 3) as i64 - 1;
+                    offset /= 3;
+                }
+                self.cells.get(&cell)
+            })
+            .flatten()
+            .cloned()
+    }
+
+    /// Indices of the indexed points at distance `dist` from `point`, within `EPS`.
+    pub fn at_distance<'s>(
+        &'s self,
+        point: &Point<f64, D>,
+        dist: f64,
+    ) -> impl Iterator<Item = usize> + use<'s, 'a, D> {
+        assert!(
+            dist + EPS <= self.cell_size,
+            "query distance exceeds the cell size"
+        );
+        let point = *point;
+        self.candidates(&point)
+            .filter(move |&i| (self.points[i] - point).norm().abs_diff_eq(&dist, EPS))
+    }
+
+    /// All pairs `(i, j)` with `j < i` of indexed points at distance `dist` within `EPS`,
+    /// sorted in lexicographic order.
+    pub fn pairs_at_distance(&self, dist: f64) -> Vec<(usize, usize)> {
+        let mut pairs: Vec<_> = self
+            .points
+            .iter()
+            .enumerate()
+            .flat_map(|(i, p)| {
+                self.at_distance(p, dist)
+                    .filter(move |&j| j < i)
+                    .map(move |j| (i, j))
+            })
+            .collect();
+        pairs.sort();
+        pairs
+    }
+}
+
+fn cell_of<const D: usize>(point: &Point<f64, D>, cell_size: f64) -> [i64; D] {
+    let coords: [f64; D] = point.coords.into();
+    coords.map(|x| (x / cell_size).floor() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Point3;
+    use std::f64::consts::SQRT_2;
+
+    #[test]
+    fn test_pairs_match_brute_force() {
+        let points: Vec<_> = (0..64)
+            .map(|i| Point3::new((i % 4) as f64, (i / 4 % 4) as f64, (i / 16) as f64 * 0.5))
+            .collect();
+
+        for dist in [0.5, 1.0, SQRT_2] {
+            let mut expected = vec![];
+            for i in 0..points.len() {
+                for j in 0..i {
+                    if (points[i] - points[j]).norm().abs_diff_eq(&dist, EPS) {
+                        expected.push((i, j));
+                    }
+                }
+            }
+            let index = SpatialHash::new(&points, dist);
+            assert_eq!(index.pairs_at_distance(dist), expected);
+        }
+    }
+}
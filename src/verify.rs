@@ -0,0 +1,76 @@
+mod interval;
+
+use crate::Node;
+use crate::graph::Graph;
+use crate::spatial::SpatialHash;
+pub use interval::Interval;
+use nalgebra::Point;
+
+/// Result of [`verify_unit_distances`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Verification {
+    /// Edges whose distance interval does not contain the target distance.
+    pub suspicious_edges: Vec<(Node, Node)>,
+    /// Non-edges whose distance interval contains the target distance.
+    pub ambiguous_non_edges: Vec<(Node, Node)>,
+}
+
+impl Verification {
+    /// Whether every edge is certainly a candidate unit-distance pair and every non-edge is
+    /// certainly not one.
+    pub fn is_certified(&self) -> bool {
+        self.suspicious_edges.is_empty() && self.ambiguous_non_edges.is_empty()
+    }
+}
+
+/// Encloses the squared distance between the true positions of two points.
+fn dist_sq_interval<const D: usize>(p: &Point<f64, D>, q: &Point<f64, D>, radius: f64) -> Interval {
+    (0..D)
+        .map(|k| (Interval::around(p[k], radius) - Interval::around(q[k], radius)).square())
+        .fold(Interval::point(0.0), |acc, x| acc + x)
+}
+
+/// Checks a unit-distance graph built from float points with rigorous interval arithmetic.
+///
+/// Each coordinate of `points` is assumed to be within `radius` of the true coordinate, e.g. a
+/// bound on the rounding error accumulated by the rotations that produced it. The squared
+/// distance of every pair of points is enclosed in an interval, and compared to `dist_sq`, an
+/// interval enclosing the squared target distance (use [`Interval::point`] when it is exactly
+/// representable, like `2.0` for [`crate::constants::DIST`]).
+///
+/// Only pairs within roughly the target distance are considered as non-edges, the others
+/// can not possibly be at the target distance.
+///
+/// # Returns
+///
+/// A [`Verification`] listing the edges of `graph` that may not be at the target distance,
+/// and the non-edges that may be.
+pub fn verify_unit_distances<G: Graph, const D: usize>(
+    points: &[Point<f64, D>],
+    graph: &G,
+    dist_sq: Interval,
+    radius: f64,
+) -> Verification {
+    let mut res = Verification::default();
+    let is_edge = |i: Node, j: Node| graph.neighbors(i).contains(&j);
+
+    for (i, j) in graph.edges() {
+        if j < i && !dist_sq_interval(&points[i], &points[j], radius).intersects(&dist_sq) {
+            res.suspicious_edges.push((i, j));
+        }
+    }
+
+    // every pair whose interval may meet `dist_sq` is within this distance
+    let reach = dist_sq.hi.sqrt() + 4.0 * radius;
+    let index = SpatialHash::new(points, reach);
+    for (i, p) in points.iter().enumerate() {
+        for j in index.candidates(p).filter(|&j| j < i) {
+            if !is_edge(i, j) && dist_sq_interval(p, &points[j], radius).intersects(&dist_sq) {
+                res.ambiguous_non_edges.push((i, j));
+            }
+        }
+    }
+    res.ambiguous_non_edges.sort();
+
+    res
+}
@@ -0,0 +1,87 @@
+use std::ops::{Add, Mul, Sub};
+
+/// A closed interval `[lo, hi]` of reals with outward rounded arithmetic.
+///
+/// Every operation rounds the lower bound down and the upper bound up by one ulp, which is
+/// enough to absorb the round-to-nearest error of the float operation. The result is therefore
+/// guaranteed to contain the exact result for any reals taken from the operands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Interval {
+    pub fn new(lo: f64, hi: f64) -> Self {
+        assert!(lo <= hi, "empty interval [{lo}, {hi}]");
+        Self { lo, hi }
+    }
+
+    /// The degenerate interval containing exactly `value`.
+    pub fn point(value: f64) -> Self {
+        Self::new(value, value)
+    }
+
+    /// The interval `[value - radius, value + radius]`, rounded outwards.
+    pub fn around(value: f64, radius: f64) -> Self {
+        Self::new(value - radius, value + radius).widen()
+    }
+
+    fn widen(self) -> Self {
+        Self {
+            lo: self.lo.next_down(),
+            hi: self.hi.next_up(),
+        }
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        self.lo <= value && value <= self.hi
+    }
+
+    pub fn intersects(&self, other: &Interval) -> bool {
+        self.lo <= other.hi && other.lo <= self.hi
+    }
+
+    /// Encloses `{x² | x ∈ self}`, which is tighter than `self * self` around zero.
+    pub fn square(self) -> Self {
+        let (a, b) = (self.lo.abs(), self.hi.abs());
+        let lo = if self.contains(0.0) { 0.0 } else { a.min(b) };
+        let hi = a.max(b);
+        Self {
+            lo: (lo * lo).next_down().max(0.0),
+            hi: (hi * hi).next_up(),
+        }
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, rhs: Interval) -> Interval {
+        Interval::new(self.lo + rhs.lo, self.hi + rhs.hi).widen()
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, rhs: Interval) -> Interval {
+        Interval::new(self.lo - rhs.hi, self.hi - rhs.lo).widen()
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+
+    fn mul(self, rhs: Interval) -> Interval {
+        let products = [
+            self.lo * rhs.lo,
+            self.lo * rhs.hi,
+            self.hi * rhs.lo,
+            self.hi * rhs.hi,
+        ];
+        let lo = products.iter().cloned().fold(f64::INFINITY, f64::min);
+        let hi = products.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        Interval::new(lo, hi).widen()
+    }
+}
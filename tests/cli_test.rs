@@ -0,0 +1,166 @@
+use ml_cnp::cli::{CliError, run};
+use ml_cnp::graph::Graph;
+use ml_cnp::io::{load_graph, load_layer};
+use ml_cnp::search::moser_spindle;
+use std::path::PathBuf;
+
+/// A path in the temporary directory, unique to this process.
+fn temp(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ml-cnp-cli-{}-{name}", std::process::id()))
+}
+
+fn run_args(args: &[&str]) -> Result<(), CliError> {
+    run(&args.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+}
+
+#[test]
+fn test_cli_usage() {
+    for args in [
+        &[][..],
+        &["solve"],
+        &["color", "graph.txt", "--colour", "3"],
+        &["color", "graph.txt", "--colors"],
+        &["color", "graph.txt", "--colors", "x", "--output", "out.txt"],
+        &["color", "--output", "out.txt"],
+        &["analyze", "graph.txt"],
+        &["convert", "a.txt", "b.txt", "c.txt"],
+        &["search", "--preset", "triangle", "--seed", "seed.xyz"],
+        &[
+            "search", "--seed", "seed.xyz", "--dist", "nan", "--output", "o",
+        ],
+        &["color", "points.xyz", "--dist", "-1", "--output", "o"],
+        &["analyze", "points.xyz", "--dist", "inf", "--output", "o"],
+        &["convert", "points.xyz", "graph.txt", "--dist", "0"],
+        // options that would be ignored
+        &[
+            "search", "--preset", "triangle", "--dist", "2", "--output", "o",
+        ],
+        &[
+            "search", "--preset", "triangle", "--found", "f", "--output", "o",
+        ],
+        &[
+            "search",
+            "--preset",
+            "triangle",
+            "--timeout",
+            "1",
+            "--output",
+            "o",
+        ],
+        &[
+            "color",
+            "graph.txt",
+            "--algorithm",
+            "dsatur",
+            "--timeout",
+            "1",
+            "--output",
+            "o",
+        ],
+        &[
+            "color",
+            "graph.txt",
+            "--algorithm",
+            "dsatur",
+            "--symmetry-breaking",
+            "--output",
+            "o",
+        ],
+    ] {
+        assert!(
+            matches!(run_args(args), Err(CliError::Usage(_))),
+            "{args:?}"
+        );
+    }
+}
+
+#[test]
+fn test_cli_pipeline() {
+    let (points, graph, dimacs) = (
+        temp("spindle.sym"),
+        temp("spindle.txt"),
+        temp("spindle.col"),
+    );
+    let mut desc = String::new();
+    for p in moser_spindle() {
+        desc += &format!("{}, {}\n", p.x, p.y);
+    }
+    std::fs::write(&points, desc).unwrap();
+
+    let path = |p: &PathBuf| p.to_str().unwrap().to_string();
+    run_args(&["convert", &path(&points), &path(&graph)]).unwrap();
+    run_args(&["convert", &path(&graph), &path(&dimacs)]).unwrap();
+    let spindle = load_graph(&dimacs).unwrap();
+    assert_eq!(spindle.size(), 7);
+    assert_eq!(spindle.edges().len(), 22);
+
+    // the Moser spindle needs 4 colors
+    let colors = temp("spindle.colors");
+    run_args(&[
+        "color",
+        &path(&graph),
+        "--colors",
+        "4",
+        "--output",
+        &path(&colors),
+    ])
+    .unwrap();
+    let coloring = std::fs::read_to_string(&colors).unwrap();
+    assert_eq!(coloring.lines().count(), 8);
+    assert!(matches!(
+        run_args(&[
+            "color",
+            &path(&points),
+            "--colors",
+            "3",
+            "--output",
+            &path(&colors)
+        ]),
+        Err(CliError::Failed(_))
+    ));
+
+    let analysis = temp("spindle.analysis");
+    run_args(&["analyze", &path(&graph), "--output", &path(&analysis)]).unwrap();
+    let report = std::fs::read_to_string(&analysis).unwrap();
+    assert!(report.contains("nodes: 7\nedges: 11\n"));
+    assert!(report.contains("clique number: 3\nindependence number: 2\n"));
+
+    for file in [points, graph, dimacs, colors, analysis] {
+        std::fs::remove_file(file).unwrap();
+    }
+}
+
+#[test]
+fn test_cli_search() {
+    let output = temp("search.layer");
+    let found = temp("found.layer");
+    run_args(&[
+        "search",
+        "--preset",
+        "triangle",
+        "--layers",
+        "2",
+        "--width",
+        "5",
+        "--colors",
+        "3",
+        "--timeout",
+        "10",
+        "--found",
+        found.to_str().unwrap(),
+        "--output",
+        output.to_str().unwrap(),
+    ])
+    .unwrap();
+    let frontier = load_layer::<2>(&output).unwrap();
+    assert!(!frontier.is_empty() && frontier.len() <= 5);
+    // the Moser spindle is found at depth 2
+    assert!(
+        load_layer::<2>(&found)
+            .unwrap()
+            .iter()
+            .any(|record| record.points.len() == 7)
+    );
+    std::fs::remove_file(output).unwrap();
+    std::fs::remove_file(found).unwrap();
+}
@@ -1,9 +1,10 @@
 use ml_cnp::algo::{ColorAlgorithm, HeuristicColoring};
 use ml_cnp::build_graph_from_str;
+use std::time::Duration;
 
 #[test]
 fn test_heuristic() {
-    let graph = build_graph_from_str(include_str!("easy.txt"));
+    let graph = build_graph_from_str(include_str!("easy.txt")).unwrap();
     let mut algo = HeuristicColoring::create(2, graph.clone());
     let res = algo.color();
     assert_eq!(res, None);
@@ -13,3 +14,34 @@ fn test_heuristic() {
     assert!(res.is_some());
     assert!(algo.validate(&res.unwrap()));
 }
+
+#[test]
+fn test_heuristic_symmetry_breaking() {
+    let graph = build_graph_from_str(include_str!("easy.txt")).unwrap();
+    let mut algo = HeuristicColoring::create(2, graph.clone()).with_symmetry_breaking(true);
+    assert_eq!(algo.color(), None);
+
+    let mut algo = HeuristicColoring::create(3, graph.clone()).with_symmetry_breaking(true);
+    let res = algo.color();
+    assert!(res.is_some());
+    assert!(algo.validate(&res.unwrap()));
+
+    // node 0 is next to the clique 1 2 3, so its colors are narrowed before the search
+    let graph = build_graph_from_str("5\n1 2\n2 3\n1 3\n0 1").unwrap();
+    let mut algo = HeuristicColoring::create(3, graph).with_symmetry_breaking(true);
+    let res = algo.color();
+    assert!(res.is_some());
+    assert!(algo.validate(&res.unwrap()));
+}
+
+#[test]
+fn test_heuristic_timeout() {
+    let graph = build_graph_from_str(include_str!("easy.txt")).unwrap();
+    let mut algo = HeuristicColoring::create(3, graph.clone()).with_timeout(Duration::ZERO);
+    assert_eq!(algo.color(), None);
+    assert!(algo.timed_out());
+
+    let mut algo = HeuristicColoring::create(3, graph).with_timeout(Duration::from_secs(60));
+    assert!(algo.color().is_some());
+    assert!(!algo.timed_out());
+}
@@ -1,11 +1,231 @@
 use ml_cnp::build_graph_from_str;
-use ml_cnp::graph::Graph;
+use ml_cnp::constants::DIST;
+use ml_cnp::graph::clique::{
+    independence_bound, independence_number, max_clique, max_independent_set,
+    max_weight_independent_set,
+};
+use ml_cnp::graph::fractional::{fractional_chromatic, independence_ratio};
+use ml_cnp::graph::hash::{Hashable, Rounds, WLHasher};
+use ml_cnp::graph::iso::is_isomorphic;
+use ml_cnp::graph::spectral::Spectrum;
+use ml_cnp::graph::theta::lovasz_theta_complement;
+use ml_cnp::graph::{Graph, VecVecGraph};
+use ml_cnp::search::moser_spindle;
+use ml_cnp::verify::{Interval, verify_unit_distances};
+use nalgebra::Point3;
 
 #[test]
 fn test_graph() {
-    let graph = build_graph_from_str(include_str!("easy.txt"));
+    let graph = build_graph_from_str(include_str!("easy.txt")).unwrap();
     assert_eq!(graph.size(), 5);
     assert_eq!(graph.neighbors(0), &[1, 4]);
     assert_eq!(graph.neighbors(1), &[0, 2]);
     assert_eq!(graph.neighbors(2), &[1, 3]);
 }
+
+#[test]
+fn test_isomorphism() {
+    let cycle = build_graph_from_str("6\n0 1\n1 2\n2 3\n3 4\n4 5\n5 0").unwrap();
+    let shuffled = build_graph_from_str("6\n3 0\n0 5\n5 1\n1 4\n4 2\n2 3").unwrap();
+    let triangles = build_graph_from_str("6\n0 1\n1 2\n2 0\n3 4\n4 5\n5 3").unwrap();
+
+    assert!(is_isomorphic(cycle.as_ref(), shuffled.as_ref()));
+    // C6 and two triangles share their WL hash, but are not isomorphic
+    assert_eq!(cycle.hash(), triangles.hash());
+    assert!(!is_isomorphic(cycle.as_ref(), triangles.as_ref()));
+}
+
+#[test]
+fn test_verify_unit_distances() {
+    let mut points = vec![
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 1.0, 0.0),
+        Point3::new(1.0, 0.0, 1.0),
+        Point3::new(0.0, 1.0, 1.0),
+    ];
+    let dist_sq = Interval::point(2.0);
+    let graph = VecVecGraph::from_points(&points, DIST);
+    assert_eq!(graph.edges().len(), 12);
+
+    let res = verify_unit_distances(&points, &graph, dist_sq, 1e-12);
+    assert!(res.is_certified());
+
+    // move a point by less than `EPS`: the graph is the same, but the edges are not certain
+    points[3].x += 1e-9;
+    let graph = VecVecGraph::from_points(&points, DIST);
+    let res = verify_unit_distances(&points, &graph, dist_sq, 1e-12);
+    assert_eq!(res.suspicious_edges, vec![(3, 1), (3, 2)]);
+
+    // with a large enough error radius they are ambiguous again
+    let res = verify_unit_distances(&points, &VecVecGraph::new(4), dist_sq, 1e-6);
+    assert_eq!(res.ambiguous_non_edges.len(), 6);
+}
+
+#[test]
+fn test_spectral_bounds() {
+    // the 5-cycle has eigenvalues 2 and 2cos(4π/5) ≈ -1.618, so Hoffman's bound is 2.24
+    let cycle = build_graph_from_str(include_str!("easy.txt")).unwrap();
+    let spectrum = Spectrum::new(cycle.as_ref());
+    assert!((spectrum.max_eigenvalue() - 2.0).abs() < 1e-9);
+    assert!((spectrum.hoffman_bound() - (1.0 + 2.0 / 1.618033988749895)).abs() < 1e-9);
+    assert_eq!(spectrum.lower_bound(), 3);
+    assert_eq!(spectrum.upper_bound(), 3);
+
+    let spindle = VecVecGraph::from_points(&moser_spindle(), 1.0);
+    let spectrum = Spectrum::new(&spindle);
+    assert!(spectrum.lower_bound() <= 4);
+    assert!(spectrum.upper_bound() >= 4);
+    assert!(spectrum.inertia_bound() <= 4.0);
+    assert!(spectrum.laplacian_bound() <= 4.0);
+}
+
+#[test]
+fn test_fractional_chromatic() {
+    let spindle = VecVecGraph::from_points(&moser_spindle(), 1.0);
+    let coloring = fractional_chromatic(&spindle).unwrap();
+    assert!((coloring.value - 3.5).abs() < 1e-9);
+
+    // the sets form a fractional coloring, and the node weights a matching dual solution
+    let total: f64 = coloring.sets.iter().map(|(_, x)| x).sum();
+    assert!((total - coloring.value).abs() < 1e-9);
+    for node in 0..spindle.size() {
+        let cover: f64 = coloring
+            .sets
+            .iter()
+            .filter(|(set, _)| set.contains(&node))
+            .map(|(_, x)| x)
+            .sum();
+        assert!(cover >= 1.0 - 1e-9);
+    }
+    for (set, _) in &coloring.sets {
+        assert!(
+            set.iter()
+                .all(|&a| set.iter().all(|&b| !spindle.neighbors(a).contains(&b)))
+        );
+    }
+    let dual: f64 = coloring.node_weights.iter().sum();
+    assert!((dual - coloring.value).abs() < 1e-9);
+}
+
+#[test]
+fn test_lovasz_theta() {
+    // ω ≤ θ(complement) ≤ χ_f, with clique number 3 and fractional chromatic number 3.5
+    let spindle = VecVecGraph::from_points(&moser_spindle(), 1.0);
+    let theta = lovasz_theta_complement(&spindle);
+    assert!(theta.violation < 1e-4);
+    assert!(theta.value > 3.0 + 1e-3 && theta.value < 3.5);
+    assert!(theta.value >= Spectrum::new(&spindle).hoffman_bound() - 1e-4);
+}
+
+#[test]
+fn test_max_clique() {
+    let spindle = VecVecGraph::from_points(&moser_spindle(), 1.0);
+    let clique = max_clique(&spindle);
+    assert_eq!(clique.len(), 3);
+    for &a in &clique {
+        assert!(
+            clique
+                .iter()
+                .all(|&b| a == b || spindle.neighbors(a).contains(&b))
+        );
+    }
+
+    // 7 nodes with at most 2 in a color class need 4 colors
+    let independent = max_independent_set(&spindle);
+    assert_eq!(independent.len(), 2);
+    assert_eq!(independence_number(&spindle), 2);
+    assert_eq!(independence_bound(&spindle), 4);
+
+    let (set, weight) = max_weight_independent_set(&spindle, &[1.0; 7]);
+    assert_eq!(set.len(), 2);
+    assert!((weight - 2.0).abs() < 1e-9);
+    // the two tips are adjacent
+    let (set, weight) = max_weight_independent_set(&spindle, &[0.0, 0.0, 0.0, 4.0, 0.0, 0.0, 5.0]);
+    assert_eq!(set, vec![6]);
+    assert!((weight - 5.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_independence_ratio() {
+    let spindle = VecVecGraph::from_points(&moser_spindle(), 1.0);
+    let ratio = independence_ratio(&spindle).unwrap();
+    assert!((ratio.ratio - 3.5).abs() < 1e-9);
+    assert!(ratio.weights.iter().all(|&w| w >= 0.0));
+    assert!((ratio.weights.iter().sum::<f64>() - ratio.total).abs() < 1e-9);
+    assert!(ratio.forces(3) && !ratio.forces(4));
+    assert!((ratio.margin(4) - 0.5).abs() < 1e-9);
+
+    let set = &ratio.heaviest_set;
+    assert!(
+        set.iter()
+            .all(|&a| set.iter().all(|&b| !spindle.neighbors(a).contains(&b)))
+    );
+    let weight: f64 = set.iter().map(|&v| ratio.weights[v]).sum();
+    assert!((weight - ratio.heaviest_weight).abs() < 1e-9);
+}
+
+#[test]
+fn test_wl_hasher_labels() {
+    // a path of 4 nodes, labelled at one end or in the middle
+    let path = build_graph_from_str("4\n0 1\n1 2\n2 3").unwrap();
+    let hasher = WLHasher::default();
+    let end = hasher.clone().with_vertex_labels(vec![1, 0, 0, 0]);
+    let mirrored = hasher.clone().with_vertex_labels(vec![0, 0, 0, 1]);
+    let middle = hasher.clone().with_vertex_labels(vec![0, 1, 0, 0]);
+    assert_eq!(end.hash(path.as_ref()), mirrored.hash(path.as_ref()));
+    assert_ne!(end.hash(path.as_ref()), middle.hash(path.as_ref()));
+    assert_ne!(end.hash(path.as_ref()), hasher.hash(path.as_ref()));
+
+    // edge labels are undirected, and unlabelled edges get label 0
+    let outer = hasher.clone().with_edge_labels([((1, 0), 7)]);
+    let inner = hasher.clone().with_edge_labels([((1, 2), 7)]);
+    assert_eq!(
+        outer.hash(path.as_ref()),
+        hasher
+            .clone()
+            .with_edge_labels([((3, 2), 7)])
+            .hash(path.as_ref())
+    );
+    assert_ne!(outer.hash(path.as_ref()), inner.hash(path.as_ref()));
+    let explicit = hasher.clone().with_edge_labels([((0, 1), 7), ((2, 3), 0)]);
+    assert_eq!(outer.hash(path.as_ref()), explicit.hash(path.as_ref()));
+}
+
+#[test]
+#[should_panic(expected = "expected a vertex label per node")]
+fn test_wl_hasher_label_count() {
+    let path = build_graph_from_str("4\n0 1\n1 2\n2 3").unwrap();
+    WLHasher::default()
+        .with_vertex_labels(vec![1, 0])
+        .hash(path.as_ref());
+}
+
+#[test]
+fn test_wl_hasher_rounds() {
+    // a long path needs many rounds to tell its nodes apart
+    let mut desc = "12".to_string();
+    for i in 0..11 {
+        desc += &format!("\n{i} {}", i + 1);
+    }
+    let path = build_graph_from_str(&desc).unwrap();
+    let classes = |rounds| {
+        let mut labels = WLHasher::new(rounds).labels(path.as_ref());
+        labels.sort();
+        labels.dedup();
+        labels.len()
+    };
+    assert_eq!(classes(Rounds::Fixed(0)), 2);
+    assert_eq!(classes(Rounds::Fixed(1)), 3);
+    // the nodes are told apart up to the reflection of the path
+    assert_eq!(classes(Rounds::UntilStable), 6);
+
+    // stable hashes still agree on isomorphic graphs
+    let mut shuffled = "12".to_string();
+    for i in 0..11 {
+        shuffled += &format!("\n{} {}", 5 * i % 12, 5 * (i + 1) % 12);
+    }
+    let relabelled = build_graph_from_str(&shuffled).unwrap();
+    let hasher = WLHasher::new(Rounds::UntilStable);
+    assert!(is_isomorphic(path.as_ref(), relabelled.as_ref()));
+    assert_eq!(hasher.hash(path.as_ref()), hasher.hash(relabelled.as_ref()));
+}
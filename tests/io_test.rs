@@ -0,0 +1,314 @@
+use ml_cnp::algo::dsatur;
+use ml_cnp::constants::{DIST, INIT_POINTS};
+use ml_cnp::exact::{ExactPoint, Real};
+use ml_cnp::graph::{Graph, VecVecGraph};
+use ml_cnp::io::{
+    Checkpoint, Format, GraphFormat, IoError, LayerRecord, PointFormat, SearchSettings,
+    layer_records, load_layer, parse_exact_points, parse_graph, parse_graph_as, parse_points,
+    points_dimension, read_checkpoint, read_layer_binary, read_layer_text, save_layer, to_graph6,
+    to_sparse6, write_checkpoint, write_graph, write_graphml, write_layer_binary, write_layer_text,
+    write_points,
+};
+use ml_cnp::search::{Dedup, SearchConfig, Selection, moser_spindle, next_layer};
+use nalgebra::Point3;
+
+#[test]
+fn test_layer_round_trip() {
+    let layer = next_layer(&[INIT_POINTS.to_vec()]);
+    let mut records = layer_records(&layer, DIST, 1);
+    records.insert(0, LayerRecord::new(0, INIT_POINTS.to_vec(), DIST));
+    for record in &mut records[1..] {
+        *record = record.clone().with_parent(0, "axis-rotation");
+    }
+
+    let dir = std::env::temp_dir();
+    for (format, name) in [(Format::Text, "layer.txt"), (Format::Binary, "layer.bin")] {
+        let path = dir.join(format!("ml-cnp-{}-{name}", std::process::id()));
+        save_layer(&path, &records, format).unwrap();
+        let loaded = load_layer::<3>(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, records);
+    }
+}
+
+#[test]
+fn test_layer_errors() {
+    let text = "ml-cnp-layer 1\ndimension 3\nrecord 0 - - 0 1\n0 0\n";
+    match read_layer_text::<3>(text) {
+        Err(IoError::Format { line, .. }) => assert_eq!(line, 4),
+        other => panic!("unexpected {other:?}"),
+    }
+    assert!(matches!(
+        read_layer_text::<2>("ml-cnp-layer 99\ndimension 2\n"),
+        Err(IoError::Version(99))
+    ));
+    assert!(read_layer_text::<2>("ml-cnp-layer 1\ndimension 3\n").is_err());
+
+    let records = vec![LayerRecord::new(0, INIT_POINTS.to_vec(), DIST)];
+    let mut bytes = vec![];
+    write_layer_binary(&mut bytes, &records).unwrap();
+    assert_eq!(
+        read_layer_binary::<3, _>(&mut bytes.as_slice()).unwrap(),
+        records
+    );
+    bytes.pop();
+    assert!(matches!(
+        read_layer_binary::<3, _>(&mut bytes.as_slice()),
+        Err(IoError::Io(_))
+    ));
+
+    // a corrupt generator length does not allocate, the file just ends
+    let mut bytes = vec![];
+    write_layer_binary(&mut bytes, &records).unwrap();
+    bytes[48..52].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(
+        read_layer_binary::<3, _>(&mut bytes.as_slice()),
+        Err(IoError::Io(_))
+    ));
+
+    // names that the text format can not hold are rejected by both formats
+    for name in ["", "-", "axis rotation"] {
+        let records = vec![records[0].clone().with_parent(0, name)];
+        assert!(write_layer_binary(&mut vec![], &records).is_err());
+        assert!(write_layer_text(&mut vec![], &records).is_err());
+    }
+}
+
+#[test]
+fn test_checkpoint_round_trip() {
+    let search = ml_cnp::search::BeamSearch::new(1).run(&[INIT_POINTS.to_vec()]);
+    let checkpoint = Checkpoint {
+        dist: DIST,
+        settings: SearchSettings {
+            depth: 3,
+            widths: vec![None, Some(10), Some(5)],
+            dedup: Dedup::Checked,
+            moves: vec!["axis-rotation".to_string(), "reflection".to_string()],
+            scorer: "edge-density".to_string(),
+            selection: Selection::Diverse {
+                max_similarity: 0.9,
+            },
+        },
+        depth: 2,
+        expanded: 1,
+        frontier: search.frontier,
+        candidates: vec![LayerRecord::new(0, INIT_POINTS.to_vec(), DIST)],
+        seen: vec![1, 2, 3],
+        layers: search.layers,
+    };
+
+    let mut bytes = vec![];
+    write_checkpoint(&mut bytes, &checkpoint).unwrap();
+    assert_eq!(
+        read_checkpoint::<3, _>(&mut bytes.as_slice()).unwrap(),
+        checkpoint
+    );
+}
+
+#[test]
+fn test_parse_graph() {
+    let native = "# a path\n4\n0 1\n1 2\n\n2 3\n";
+    let dimacs = "c a path\nc\np edge 4 3\ne 1 2\ne 2 3\ne 3 4\n";
+    let edges = "% a path\n0 1\n1 2  \n2 3\n1 0\n";
+    assert_eq!(GraphFormat::detect(native), GraphFormat::Native);
+    assert_eq!(GraphFormat::detect(dimacs), GraphFormat::Dimacs);
+    assert_eq!(GraphFormat::detect(edges), GraphFormat::EdgeList);
+    for desc in [native, dimacs, edges] {
+        let graph = parse_graph(desc).unwrap();
+        assert_eq!(graph.size(), 4);
+        // the repeated edge is added once
+        assert_eq!(graph.edges().len(), 6);
+        assert_eq!(graph.neighbors(1), &[0, 2]);
+    }
+
+    let error = |desc: &str, format| parse_graph_as(desc, format).err().unwrap().to_string();
+    assert_eq!(
+        error("3\n0 1\n1 x", GraphFormat::Native),
+        "line 3: invalid node `x`"
+    );
+    assert_eq!(
+        error("3\n0 1\n1 1", GraphFormat::Native),
+        "line 3: loop on node 1"
+    );
+    assert_eq!(
+        error("3\n\n0 1 2", GraphFormat::Native),
+        "line 3: expected an edge `<from> <to>`"
+    );
+    assert_eq!(
+        error("c\ne 1 2", GraphFormat::Dimacs),
+        "line 2: edge before the problem line `p edge`"
+    );
+    assert_eq!(
+        error("p edge 2 1\ne 0 1", GraphFormat::Dimacs),
+        "line 2: DIMACS nodes are numbered from 1"
+    );
+    assert_eq!(
+        error("p edge 2 1\ne 1 3", GraphFormat::Dimacs),
+        "line 2: node 3 out of range for 2 nodes"
+    );
+    assert_eq!(
+        error("0 1\n0 99999999999999", GraphFormat::EdgeList),
+        "line 2: 100000000000000 nodes is more than the supported 1048576"
+    );
+    assert_eq!(
+        error("c\np edge 99999999999 0", GraphFormat::Dimacs),
+        "line 2: 99999999999 nodes is more than the supported 1048576"
+    );
+    assert_eq!(
+        error("99999999999", GraphFormat::Native),
+        "line 1: 99999999999 nodes is more than the supported 1048576"
+    );
+    assert_eq!(
+        error("", GraphFormat::Dimacs),
+        "missing problem line `p edge`"
+    );
+}
+
+#[test]
+fn test_write_graph() {
+    let spindle = VecVecGraph::from_points(&moser_spindle(), 1.0);
+    for format in [
+        GraphFormat::Native,
+        GraphFormat::Dimacs,
+        GraphFormat::EdgeList,
+        GraphFormat::Graph6,
+    ] {
+        let mut out = vec![];
+        write_graph(&mut out, &spindle, format).unwrap();
+        let desc = String::from_utf8(out).unwrap();
+        assert_eq!(GraphFormat::detect(&desc), format);
+        let mut read = parse_graph(&desc).unwrap().edges();
+        let mut edges = spindle.edges();
+        read.sort();
+        edges.sort();
+        assert_eq!(read, edges);
+    }
+
+    // sizes above 62 take more characters
+    let mut large = VecVecGraph::new(100);
+    for i in 0..99 {
+        large.add_edge(i, i + 1);
+    }
+    let graph6 = to_graph6(&large);
+    assert!(graph6.starts_with('~'));
+    let read = parse_graph(&format!(">>graph6<<{graph6}")).unwrap();
+    assert_eq!(read.size(), 100);
+    assert_eq!(read.edges().len(), 198);
+    assert!(parse_graph_as("Bw?", GraphFormat::Graph6).is_err());
+    assert_eq!(
+        parse_graph_as(">>graph6<<~~~~~~~~", GraphFormat::Graph6)
+            .err()
+            .unwrap()
+            .to_string(),
+        "line 1: 68719476735 nodes is more than the supported 1048576"
+    );
+
+    // the 4 byte size form ends at 258047 nodes
+    assert!(to_sparse6(&VecVecGraph::new(258047)).starts_with(":~}~~"));
+    assert!(to_sparse6(&VecVecGraph::new(258048)).starts_with(":~~???~??"));
+
+    let mut single = VecVecGraph::new(2);
+    single.add_edge(0, 1);
+    assert_eq!(to_sparse6(&single), ":An");
+
+    let mut graphml = vec![];
+    write_graphml(&mut graphml, &spindle, Some(&dsatur(&spindle))).unwrap();
+    let graphml = String::from_utf8(graphml).unwrap();
+    assert_eq!(graphml.matches("<node ").count(), 7);
+    assert_eq!(graphml.matches("<edge ").count(), 11);
+    assert!(graphml.contains(r#"<data key="color">"#));
+    assert_eq!(
+        write_graphml(&mut vec![], &spindle, Some(&[0, 1]))
+            .err()
+            .unwrap()
+            .to_string(),
+        "2 colors for 7 nodes"
+    );
+}
+
+#[test]
+fn test_point_formats() {
+    let spindle = moser_spindle();
+    for format in [PointFormat::Xyz, PointFormat::Json, PointFormat::Symbolic] {
+        let mut out = vec![];
+        write_points(&mut out, &spindle, format).unwrap();
+        let desc = String::from_utf8(out).unwrap();
+        assert_eq!(PointFormat::detect(&desc), format);
+        assert_eq!(parse_points::<2>(&desc, format).unwrap(), spindle);
+    }
+
+    // a unit rhombus given symbolically, read exactly
+    let rhombus = "# two triangles\n0, 0, 0\n1, 0, 0\n1/2, sqrt(3)/2, 0\n1/2, -sqrt(3)/2, 0";
+    let exact = parse_exact_points(rhombus, PointFormat::Symbolic).unwrap();
+    let graph = VecVecGraph::from_exact_points(&exact, &"1".parse().unwrap());
+    assert_eq!(graph.edges().len(), 10);
+    let points: Vec<Point3<f64>> = exact.iter().map(ExactPoint::to_point).collect();
+    assert_eq!(VecVecGraph::from_points(&points, 1.0).edges().len(), 10);
+
+    let json = r#"{"dimension": 3, "points": [[0, 0, 0], ["1/2", "sqrt(3)/2", 0]]}"#;
+    let points = parse_points::<3>(json, PointFormat::Json).unwrap();
+    assert!((points[1].coords.norm() - 1.0).abs() < 1e-15);
+
+    let error = |desc: &str, format| parse_points::<3>(desc, format).err().unwrap().to_string();
+    assert_eq!(
+        error("0 0 0\n1 x 0", PointFormat::Xyz),
+        "line 2: invalid number `x`"
+    );
+    assert_eq!(
+        error("0, 0\n", PointFormat::Symbolic),
+        "line 1: expected 3 coordinates, found 2"
+    );
+    assert_eq!(
+        error("0, sqrt(-1), 0", PointFormat::Symbolic),
+        "line 1: invalid coordinate `sqrt(-1)`: no square root of -1 at column 9"
+    );
+    assert_eq!(
+        error("{\"dimension\": 2,\n\"points\": []}", PointFormat::Json),
+        "line 1: expected dimension 3, found 2"
+    );
+    assert_eq!(
+        error("[[0, 0, 0],\n[1, 0 0]]", PointFormat::Json),
+        "line 2: expected `,` or `]`"
+    );
+
+    let nested = format!("{}{}", "[".repeat(200_000), "]".repeat(200_000));
+    assert_eq!(
+        error(&nested, PointFormat::Json),
+        "line 1: arrays nested too deeply"
+    );
+    for (desc, format) in [
+        ("0 nan 0", PointFormat::Xyz),
+        ("0, inf, 0", PointFormat::Symbolic),
+        ("[[0, 0, -inf]]", PointFormat::Json),
+    ] {
+        assert!(
+            error(desc, format).contains("non-finite coordinate"),
+            "{desc}"
+        );
+    }
+
+    let json = "{\"points\": [[0, 0]], \"dimension\": 2}";
+    assert_eq!(points_dimension(json, PointFormat::Json).unwrap(), 2);
+    assert_eq!(
+        points_dimension("0, 0, 1/2, 0", PointFormat::Symbolic).unwrap(),
+        4
+    );
+    assert!(points_dimension("# nothing\n", PointFormat::Xyz).is_err());
+}
+
+#[test]
+fn test_symbolic_config() {
+    let config =
+        SearchConfig::<2>::parse("distance 1\npoint 0 0\npoint 1 0\npoint 1/2 sqrt(3)/2").unwrap();
+    assert!(((config.seed[2] - config.seed[1]).norm() - 1.0).abs() < 1e-15);
+
+    // radicands beyond u64 and deep nesting are errors, not panics
+    let product = "sqrt(999999937)*sqrt(999999929)*sqrt(999999893)";
+    let error = product.parse::<Real>().unwrap_err();
+    assert!(error.contains("number too large"), "{error}");
+    let config = format!("distance 1\npoint {product} 0");
+    assert!(SearchConfig::<2>::parse(&config).is_err());
+    let nested = format!("{}1{}", "(".repeat(200_000), ")".repeat(200_000));
+    let error = nested.parse::<Real>().unwrap_err();
+    assert!(error.contains("nested too deeply"), "{error}");
+}
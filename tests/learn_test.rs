@@ -0,0 +1,65 @@
+use ml_cnp::algo::{ColorAlgorithm, HeuristicColoring};
+use ml_cnp::learn::{FeatureExtractor, Gnn, TrainConfig, label_examples};
+use ml_cnp::search::{SearchConfig, Selection};
+use std::rc::Rc;
+
+#[test]
+fn test_gnn() {
+    let config = SearchConfig::<2>::preset("triangle").unwrap();
+    let layer2 = config.next_layer(&config.next_layer(std::slice::from_ref(&config.seed)));
+    let layer3 = config.next_layer(&layer2);
+    let graphs = layer2
+        .iter()
+        .chain(&layer3)
+        .map(|p| config.graph(p))
+        .collect();
+    let examples = label_examples(graphs, 3);
+    assert!(examples.iter().any(|e| e.uncolorable));
+    assert!(examples.iter().any(|e| !e.uncolorable));
+
+    let mut model = Gnn::new(&[8, 8], 0);
+    let before = model.loss(&examples);
+    model.train(&examples, &TrainConfig::default());
+    assert!(model.loss(&examples) < before / 2.0);
+
+    let mean = |uncolorable: bool| {
+        let scores: Vec<_> = examples
+            .iter()
+            .filter(|e| e.uncolorable == uncolorable)
+            .map(|e| model.predict(&e.graph))
+            .collect();
+        scores.iter().sum::<f64>() / scores.len() as f64
+    };
+    assert!(mean(true) > mean(false));
+
+    // the trained model ranks an uncolorable graph, the Moser spindle, first in layer 2
+    let best = config.prune_layer_by(layer2, 1, &model, Selection::TopK);
+    let graph = Rc::new(config.graph(&best[0]));
+    assert!(HeuristicColoring::create(3, graph).color().is_none());
+}
+
+#[test]
+fn test_features() {
+    let config = SearchConfig::<2>::preset("moser_spindle").unwrap();
+    let spindle = config.graph(&config.seed);
+    let extractor = FeatureExtractor::default();
+    let names = extractor.names();
+    let features = extractor.extract(&spindle);
+    assert_eq!(names.len(), features.len());
+
+    let feature = |name: &str| features[names.iter().position(|n| n == name).unwrap()];
+    assert_eq!(feature("nodes"), 7.0);
+    assert_eq!(feature("edges"), 11.0);
+    assert_eq!(feature("min_degree"), 3.0);
+    assert_eq!(feature("max_degree"), 4.0);
+    assert_eq!(feature("triangles"), 4.0);
+    assert_eq!(feature("k4s"), 0.0);
+    // the Hoffman bound is a lower bound on the chromatic number, 4
+    assert!(feature("hoffman_bound") <= 4.0);
+    let histogram: f64 = (0..16).map(|i| feature(&format!("wl_{i}"))).sum();
+    assert!((histogram - 1.0).abs() < 1e-9);
+
+    let all = extractor.extract_all(&[spindle, config.graph(&config.seed[..3])]);
+    assert_eq!(all.shape(), (2, extractor.dimension()));
+    assert_eq!(all[(1, 6)], 1.0);
+}
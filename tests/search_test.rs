@@ -0,0 +1,287 @@
+use ml_cnp::algo::{ColorAlgorithm, HeuristicColoring, dsatur_colors};
+use ml_cnp::constants::{DIST, INIT_POINTS, INIT_POINTS_2D};
+use ml_cnp::exact::{self, ExactPoint, Real};
+use ml_cnp::graph::iso::is_isomorphic;
+use ml_cnp::graph::{Graph, VecVecGraph};
+use ml_cnp::io::{load_checkpoint, save_checkpoint};
+use ml_cnp::search::{
+    AxisMove, BeamSearch, CliqueCount, Dedup, EdgeDensity, GreedyColors, Lineage, MinkowskiSum,
+    PivotRotation, Reflection, Scorer, SearchConfig, Selection, UnionWith, next_layer,
+    next_layer_checked,
+};
+use nalgebra::Point2;
+use std::rc::Rc;
+use std::time::Duration;
+
+#[test]
+fn test_next_layer_checked() {
+    let seed = [INIT_POINTS.to_vec()];
+    let layer = next_layer(&seed);
+    let (checked, stats) = next_layer_checked(&seed);
+    assert_eq!(checked.len(), layer.len() + stats.true_collisions);
+    assert!(stats.false_collisions > 0);
+}
+
+#[test]
+fn test_exact_layer() {
+    let seed = [INIT_POINTS.to_vec()];
+    let layer = next_layer(&next_layer(&seed));
+
+    let exact_seed = [INIT_POINTS.iter().map(ExactPoint::from_point).collect()];
+    let dist_sq = Real::from_integer(2);
+    let exact_layer = exact::next_layer(&exact::next_layer(&exact_seed, &dist_sq), &dist_sq);
+    assert_eq!(exact_layer.len(), layer.len());
+
+    for points in &exact_layer {
+        let exact = VecVecGraph::from_exact_points(points, &dist_sq);
+        let approx: Vec<_> = points.iter().map(ExactPoint::to_point).collect();
+        let float = VecVecGraph::from_points(&approx, DIST);
+        assert_eq!(exact.edges(), float.edges());
+    }
+}
+
+#[test]
+fn test_planar_moser_spindle() {
+    let layer = next_layer(&next_layer(&[INIT_POINTS_2D.to_vec()]));
+    let spindle = layer
+        .iter()
+        .find(|points| {
+            let graph = Rc::new(VecVecGraph::from_points(points, DIST));
+            HeuristicColoring::create(3, graph).color().is_none()
+        })
+        .expect("the Moser spindle is two layers away from a triangle");
+    assert_eq!(spindle.len(), 7);
+    assert_eq!(
+        VecVecGraph::from_points(spindle, DIST).edges().len(),
+        2 * 11
+    );
+}
+
+#[test]
+fn test_search_config() {
+    let config = SearchConfig::<2>::parse("preset moser_spindle").unwrap();
+    let graph = config.graph(&config.seed);
+    assert_eq!(graph.edges().len(), 2 * 11);
+    assert!(
+        HeuristicColoring::create(3, Rc::new(graph))
+            .color()
+            .is_none()
+    );
+
+    // the tetrahedron at unit distance gives the same layers as at distance √2
+    let config = SearchConfig::<3>::parse("preset tetrahedron").unwrap();
+    let layer = config.next_layer(&config.next_layer(std::slice::from_ref(&config.seed)));
+    assert_eq!(
+        layer.len(),
+        next_layer(&next_layer(&[INIT_POINTS.to_vec()])).len()
+    );
+
+    let err = SearchConfig::<3>::parse("distance 1\npoint 0 0").unwrap_err();
+    assert_eq!(err.line, 2);
+}
+
+#[test]
+fn test_move_generators() {
+    let config = SearchConfig::<2>::preset("triangle").unwrap();
+    let triangle = config.seed.clone();
+
+    // a triangle plus a translate by a unit vector is a prism graph
+    let segment = vec![Point2::origin(), Point2::new(0.0, 1.0)];
+    let prism = config.next_layer_with(
+        std::slice::from_ref(&triangle),
+        &[&MinkowskiSum { summand: segment }],
+    );
+    assert_eq!(prism.len(), 1);
+    assert_eq!(prism[0].len(), 6);
+    assert_eq!(config.graph(&prism[0]).edges().len(), 2 * 9);
+
+    // gluing a triangle onto a vertex and rotating it into place
+    let union = config.next_layer_with(
+        std::slice::from_ref(&triangle),
+        &[&UnionWith {
+            other: triangle.clone(),
+        }],
+    );
+    let rotations = config.next_layer(std::slice::from_ref(&triangle));
+    for points in &rotations {
+        let graph = config.graph(points);
+        assert!(
+            union
+                .iter()
+                .any(|u| is_isomorphic(&config.graph(u), &graph))
+        );
+    }
+
+    let layer = config.next_layer_with(&union, &[&PivotRotation, &Reflection]);
+    // rotating a triangle around a vertex, mapping a side onto the next, gives a rhombus
+    let rhombus = layer
+        .iter()
+        .find(|points| points.len() == 4)
+        .expect("a rhombus");
+    assert_eq!(config.graph(rhombus).edges().len(), 2 * 5);
+}
+
+#[test]
+fn test_prune_layer_scorers() {
+    let config = SearchConfig::<2>::preset("triangle").unwrap();
+    let layer = config.next_layer(&config.next_layer(std::slice::from_ref(&config.seed)));
+
+    // the Moser spindle is the only candidate that needs 4 colors
+    let best = config.prune_layer_by(layer.clone(), 1, &GreedyColors, Selection::TopK);
+    assert_eq!(dsatur_colors(&config.graph(&best[0])), 4);
+
+    let density = |points: &Vec<_>| EdgeDensity.score(&config.graph(points));
+    let pruned = config.prune_layer(layer.clone(), 3);
+    let lowest = pruned.iter().map(density).fold(f64::INFINITY, f64::min);
+    assert!(layer.iter().filter(|p| density(p) > lowest).count() < 3);
+
+    let diverse = config.prune_layer_by(
+        layer.clone(),
+        3,
+        &CliqueCount { size: 3 },
+        Selection::Diverse {
+            max_similarity: 0.5,
+        },
+    );
+    assert_eq!(diverse.len(), 3.min(layer.len()));
+}
+
+#[test]
+fn test_beam_search() {
+    let seed = [INIT_POINTS.to_vec()];
+    let full = BeamSearch::new(3).run(&seed);
+    assert_eq!(
+        full.frontier.len(),
+        next_layer(&next_layer(&next_layer(&seed))).len()
+    );
+    assert!(full.layers.iter().all(|l| l.generated == l.kept));
+
+    let beam = BeamSearch::new(3)
+        .with_layer_widths(&[None, Some(2)])
+        .with_dedup(Dedup::Global)
+        .with_report(2)
+        .run(&seed);
+    assert_eq!(beam.layers[1].kept, 2);
+    assert!(beam.layers.iter().all(|l| l.best.len() <= 2));
+    assert!(beam.layers[2].generated < full.layers[2].generated);
+    for layer in &beam.layers {
+        assert!(layer.best.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+}
+
+#[test]
+fn test_beam_search_resume() {
+    let seed = [INIT_POINTS.to_vec()];
+    let path = std::env::temp_dir().join(format!("ml-cnp-{}-beam.ckpt", std::process::id()));
+    let search = |depth| {
+        BeamSearch::new(depth)
+            .with_layer_widths(&[None, Some(3), Some(3)])
+            .with_checkpoint_interval(Duration::ZERO)
+    };
+    let full = search(3).resume(&seed, &path).unwrap();
+    assert_eq!(full, search(3).run(&seed));
+    // a finished checkpoint only gives the result
+    assert_eq!(search(3).resume(&[], &path).unwrap(), full);
+
+    // a search interrupted after depth 1 goes on from its checkpoint
+    let first = search(1).run(&seed);
+    let mut checkpoint = load_checkpoint::<3>(&path).unwrap().unwrap();
+    checkpoint.depth = 2;
+    checkpoint.expanded = 0;
+    checkpoint.frontier = first.frontier.clone();
+    checkpoint.layers.truncate(1);
+    save_checkpoint(&path, &checkpoint).unwrap();
+    assert_eq!(search(3).resume(&[], &path).unwrap(), full);
+
+    // the point sets know where they come from
+    for record in &full.frontier {
+        let step: AxisMove = record.generator.as_ref().unwrap().parse().unwrap();
+        assert!(step.target < record.points.len());
+        assert!(record.parent.is_some());
+    }
+
+    // a checkpoint of another search is rejected
+    for other in [
+        search(2),
+        search(3).with_dist(2.0),
+        search(3).with_beam_width(4),
+        search(3).with_dedup(Dedup::Global),
+        search(3).with_scorer(&GreedyColors),
+    ] {
+        assert!(other.resume(&seed, &path).is_err());
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_provenance() {
+    let config = SearchConfig::<2>::preset("triangle").unwrap();
+    let mut lineage = Lineage::new(vec![config.seed.clone()], config.dist);
+    lineage.grow();
+    lineage.grow();
+
+    // the traced layers match the untraced ones
+    let layer = config.next_layer(&config.next_layer(std::slice::from_ref(&config.seed)));
+    assert_eq!(lineage.layers()[2].len(), layer.len());
+
+    for (index, traced) in lineage.layers()[2].iter().enumerate() {
+        let construction = lineage.chain(2, index);
+        let replayed = construction.replay(lineage.seeds(), config.dist).unwrap();
+        assert_eq!(replayed, traced.points);
+
+        // the shortest construction gives the same graph, in at most as many moves
+        let minimal = lineage.minimal_chain(&traced.points).unwrap();
+        assert!(minimal.moves.len() <= 2);
+        let rebuilt = minimal.replay(lineage.seeds(), config.dist).unwrap();
+        assert!(is_isomorphic(
+            &config.graph(&rebuilt),
+            &config.graph(&traced.points)
+        ));
+    }
+
+    // the beam search records the moves as well
+    let result = config
+        .beam_search(1)
+        .run(std::slice::from_ref(&config.seed));
+    assert_eq!(result.frontier.len(), lineage.layers()[1].len());
+    for record in &result.frontier {
+        let step: AxisMove = record.generator.as_ref().unwrap().parse().unwrap();
+        assert_eq!(record.parent, Some(0));
+        assert_eq!(
+            step.apply(&config.seed, config.dist).unwrap(),
+            record.points
+        );
+    }
+
+    // moves are written so they can be read back
+    let records = lineage.records();
+    assert_eq!(records.len(), 1 + lineage.layers()[1].len() + layer.len());
+    for record in &records[1..] {
+        let step: AxisMove = record.generator.as_ref().unwrap().parse().unwrap();
+        let parent = &records[record.parent.unwrap()];
+        assert_eq!(
+            step.apply(&parent.points, config.dist).unwrap(),
+            record.points
+        );
+    }
+}
+
+#[test]
+fn test_mcts() {
+    let config = SearchConfig::<2>::preset("triangle").unwrap();
+    let search = config.mcts(3).with_iterations(30).with_seed(7);
+    let result = search.run(&config.seed);
+    assert_eq!(result, search.run(&config.seed));
+    assert!(result.nodes > 1);
+    assert!(result.best.windows(2).all(|w| w[0].score >= w[1].score));
+
+    assert!(!result.uncolorable.is_empty());
+    for points in &result.uncolorable {
+        let graph = Rc::new(config.graph(points));
+        assert!(HeuristicColoring::create(3, graph).color().is_none());
+    }
+
+    // without time to color, nothing is proved uncolorable
+    let result = search.with_timeout(Duration::ZERO).run(&config.seed);
+    assert!(result.uncolorable.is_empty());
+}